@@ -0,0 +1,172 @@
+//! Canonical [apgcodes](https://conwaylife.com/wiki/Apgcode) of spaceships.
+//!
+//! The apgcode of a spaceship with period `p` is `xq{p}_`, or `xp{p}_` for
+//! an oscillator, followed by the extended Wechsler format of one of its
+//! phases. Among all phases and all 8 orientations, the shortest encoding
+//! is chosen, with ties broken by lexicographical order.
+
+/// A two-state pattern, as rows of cells.
+type Grid = Vec<Vec<bool>>;
+
+/// The 8 orientations of a grid.
+fn orientations(grid: &Grid) -> Vec<Grid> {
+    let height = grid.len();
+    let width = grid.first().map_or(0, Vec::len);
+    let transpose: Grid = (0..width)
+        .map(|x| (0..height).map(|y| grid[y][x]).collect())
+        .collect();
+    let mut result = Vec::with_capacity(8);
+    for g in [grid.clone(), transpose] {
+        let flip_rows: Grid = g.iter().rev().cloned().collect();
+        let flip_cols: Grid = g
+            .iter()
+            .map(|row| row.iter().rev().copied().collect())
+            .collect();
+        let rotate: Grid = flip_rows
+            .iter()
+            .map(|row| row.iter().rev().copied().collect())
+            .collect();
+        result.extend([g, flip_rows, flip_cols, rotate]);
+    }
+    result
+}
+
+/// Character for a number in `0..36`.
+fn digit(n: usize) -> char {
+    std::char::from_digit(n as u32, 36).unwrap()
+}
+
+/// Encodes a run of `n` zeros.
+fn zeros(mut n: usize, code: &mut String) {
+    while n >= 4 {
+        let m = n.min(39);
+        code.push('y');
+        code.push(digit(m - 4));
+        n -= m;
+    }
+    match n {
+        1 => code.push('0'),
+        2 => code.push('w'),
+        3 => code.push('x'),
+        _ => (),
+    }
+}
+
+/// The extended Wechsler format of a grid.
+///
+/// The grid is assumed to be trimmed to its bounding box.
+pub fn wechsler(grid: &Grid) -> String {
+    let height = grid.len();
+    let width = grid.first().map_or(0, Vec::len);
+    let mut strips = Vec::new();
+    for y0 in (0..height).step_by(5) {
+        let columns: Vec<usize> = (0..width)
+            .map(|x| {
+                (0..5)
+                    .filter(|&i| y0 + i < height && grid[y0 + i][x])
+                    .map(|i| 1 << i)
+                    .sum()
+            })
+            .collect();
+        let len = columns.iter().rposition(|&c| c != 0).map_or(0, |i| i + 1);
+        let mut strip = String::new();
+        let mut run = 0;
+        for &c in &columns[..len] {
+            if c == 0 {
+                run += 1;
+            } else {
+                zeros(run, &mut strip);
+                run = 0;
+                strip.push(digit(c));
+            }
+        }
+        strips.push(strip);
+    }
+    strips.join("z")
+}
+
+/// Whether the encoding `a` is preferred over `b`.
+fn is_better(a: &str, b: &str) -> bool {
+    (a.len(), a) < (b.len(), b)
+}
+
+/// The canonical apgcode of a spaceship, or of an oscillator if it does
+/// not move, given all its phases.
+///
+/// Each phase is a grid trimmed to its bounding box.
+pub fn apgcode(phases: &[Grid], period: i32, moves: bool) -> String {
+    let mut best: Option<String> = None;
+    for phase in phases {
+        for grid in orientations(phase) {
+            let code = wechsler(&grid);
+            if best.as_ref().is_none_or(|best| is_better(&code, best)) {
+                best = Some(code);
+            }
+        }
+    }
+    let prefix = if moves { "xq" } else { "xp" };
+    format!("{}{}_{}", prefix, period, best.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verify::phases;
+
+    /// A grid from rows of `.` and `o`.
+    fn grid(rows: &[&str]) -> Grid {
+        rows.iter()
+            .map(|row| row.chars().map(|c| c == 'o').collect())
+            .collect()
+    }
+
+    /// The apgcode of a pattern in Conway's Game of Life.
+    fn apgcode_of(rows: &[&str], period: i32) -> String {
+        let mut living = Vec::new();
+        for (y, row) in grid(rows).iter().enumerate() {
+            for (x, &alive) in row.iter().enumerate() {
+                if alive {
+                    living.push((x as i32, y as i32));
+                }
+            }
+        }
+        let (phases, (dx, dy)) = phases("B3/S23", &living, period).unwrap().unwrap();
+        apgcode(&phases, period, (dx, dy) != (0, 0))
+    }
+
+    #[test]
+    fn wechsler_format() {
+        assert_eq!(wechsler(&grid(&["oo", "oo"])), "33");
+        assert_eq!(wechsler(&grid(&["o.o"])), "101");
+        assert_eq!(wechsler(&grid(&["o..o"])), "1w1");
+        assert_eq!(wechsler(&grid(&["o...o"])), "1x1");
+        assert_eq!(wechsler(&grid(&["o.....o"])), "1y11");
+        assert_eq!(wechsler(&grid(&["o", "o", "o", "o", "o", "o"])), "vz1");
+        assert_eq!(
+            wechsler(&grid(&["o.", "..", "..", "..", "..", ".o"])),
+            "1z01"
+        );
+    }
+
+    #[test]
+    fn spaceships() {
+        // Any phase and orientation gives the same apgcode.
+        assert_eq!(apgcode_of(&[".o.", "..o", "ooo"], 4), "xq4_153");
+        assert_eq!(apgcode_of(&["oo.", "o.o", "o.."], 4), "xq4_153");
+        assert_eq!(
+            apgcode_of(&[".o..o", "o....", "o...o", "oooo."], 4),
+            "xq4_6frc"
+        );
+        assert_eq!(
+            apgcode_of(&["oooo.", "o...o", "o....", ".o..o"], 4),
+            "xq4_6frc"
+        );
+    }
+
+    #[test]
+    fn oscillators() {
+        assert_eq!(apgcode_of(&["ooo"], 2), "xp2_7");
+        assert_eq!(apgcode_of(&["o", "o", "o"], 2), "xp2_7");
+        assert_eq!(apgcode_of(&["oo..", "oo..", "..oo", "..oo"], 2), "xp2_318c");
+    }
+}
@@ -0,0 +1,39 @@
+//! The budgets of `--max-time` and `--max-steps`, shared by all searches
+//! of this run, including the threads of a parallel search.
+//!
+//! They count from the start of this process, and are never saved, so a
+//! resumed search gets a new budget.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+/// The start of this run.
+static START: OnceLock<Instant> = OnceLock::new();
+
+/// Number of search steps in this run.
+static STEPS: AtomicU64 = AtomicU64::new(0);
+
+/// Starts the clock of the run.
+pub fn start() {
+    START.get_or_init(Instant::now);
+}
+
+/// The wall-clock time since the start of the run.
+pub fn elapsed() -> Duration {
+    START.get_or_init(Instant::now).elapsed()
+}
+
+/// Records the steps of a view.
+pub fn add_steps(steps: u64) {
+    STEPS.fetch_add(steps, Ordering::SeqCst);
+}
+
+/// Number of search steps in this run, in all searches.
+pub fn steps() -> u64 {
+    STEPS.load(Ordering::SeqCst)
+}
@@ -0,0 +1,222 @@
+//! Compression of the save files.
+//!
+//! This requires the `compress` feature; without it, compressed save files
+//! can neither be written nor loaded.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::{
+    io::{BufWriter, Write},
+    str::FromStr,
+};
+
+/// The first bytes of a gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// The first bytes of a zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression methods of the save files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compress {
+    /// [gzip](https://www.gzip.org/).
+    Gzip,
+    /// [Zstandard](https://facebook.github.io/zstd/).
+    Zstd,
+}
+
+impl FromStr for Compress {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gzip" => Ok(Compress::Gzip),
+            "zstd" => Ok(Compress::Zstd),
+            _ => Err(String::from("invalid compression method")),
+        }
+    }
+}
+
+impl Compress {
+    /// The suffix appended to the name of the save file.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Compress::Gzip => ".gz",
+            Compress::Zstd => ".zst",
+        }
+    }
+
+    /// The compression method of the save file with this name, if any.
+    pub fn from_path(path: &std::path::Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "gz" => Some(Compress::Gzip),
+            "zst" => Some(Compress::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Detects the compression method by the magic bytes.
+    fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&GZIP_MAGIC) {
+            Some(Compress::Gzip)
+        } else if data.starts_with(&ZSTD_MAGIC) {
+            Some(Compress::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "compress")]
+mod imp {
+    use super::Compress;
+    use anyhow::Result;
+    use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+    use serde::Serialize;
+    use std::io::{BufWriter, Read, Write};
+
+    pub fn require() -> Result<()> {
+        Ok(())
+    }
+
+    pub fn write_json<W: Write, T: Serialize>(method: Compress, writer: W, value: &T) -> Result<W> {
+        match method {
+            Compress::Gzip => {
+                let mut encoder = BufWriter::new(GzEncoder::new(writer, Compression::default()));
+                serde_json::to_writer(&mut encoder, value)?;
+                Ok(encoder.into_inner().map_err(|e| e.into_error())?.finish()?)
+            }
+            Compress::Zstd => {
+                let mut encoder = BufWriter::new(zstd::Encoder::new(writer, 0)?);
+                serde_json::to_writer(&mut encoder, value)?;
+                Ok(encoder.into_inner().map_err(|e| e.into_error())?.finish()?)
+            }
+        }
+    }
+
+    pub fn decompress(method: Compress, data: &[u8]) -> Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        match method {
+            Compress::Gzip => {
+                GzDecoder::new(data).read_to_end(&mut buffer)?;
+            }
+            Compress::Zstd => {
+                zstd::Decoder::new(data)?.read_to_end(&mut buffer)?;
+            }
+        }
+        Ok(buffer)
+    }
+}
+
+#[cfg(not(feature = "compress"))]
+mod imp {
+    use super::Compress;
+    use anyhow::{bail, Result};
+    use serde::Serialize;
+    use std::io::Write;
+
+    pub fn require() -> Result<()> {
+        bail!("This build lacks the `compress` feature.")
+    }
+
+    pub fn write_json<W: Write, T: Serialize>(_: Compress, writer: W, _: &T) -> Result<W> {
+        require().map(|_| writer)
+    }
+
+    pub fn decompress(_: Compress, _: &[u8]) -> Result<Vec<u8>> {
+        require().map(|_| Vec::new())
+    }
+}
+
+/// Fails if this build does not support compression.
+pub fn require() -> Result<()> {
+    imp::require()
+}
+
+/// Writes the value as JSON, compressed with the given method if any.
+///
+/// The JSON is streamed through the compressor, so that the whole
+/// uncompressed save file is never in the memory. Returns the writer.
+pub fn write_json<W: Write, T: Serialize>(
+    method: Option<Compress>,
+    writer: W,
+    value: &T,
+) -> Result<W> {
+    match method {
+        Some(method) => imp::write_json(method, writer, value),
+        None => {
+            let mut writer = BufWriter::new(writer);
+            serde_json::to_writer(&mut writer, value)?;
+            Ok(writer.into_inner().map_err(|e| e.into_error())?)
+        }
+    }
+}
+
+/// Decompresses the data into a string, detecting the compression method
+/// by the magic bytes. Uncompressed data is returned as is.
+pub fn decompress(data: Vec<u8>) -> Result<String> {
+    let data = match Compress::detect(&data) {
+        Some(method) => imp::decompress(method, &data)?,
+        None => data,
+    };
+    Ok(String::from_utf8(data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rlifesrc_lib::{save::WorldSer, Config};
+
+    /// The save of a large world after some steps of the search.
+    fn large_save() -> WorldSer {
+        let mut world = Config::new(64, 64, 6).set_translate(0, 1).world().unwrap();
+        world.search(Some(20_000));
+        world.ser()
+    }
+
+    #[test]
+    fn methods() {
+        assert_eq!("gzip".parse(), Ok(Compress::Gzip));
+        assert_eq!("zstd".parse(), Ok(Compress::Zstd));
+        assert!("none".parse::<Compress>().is_err());
+        for &method in &[Compress::Gzip, Compress::Zstd] {
+            let path = format!("save.json{}", method.suffix());
+            assert_eq!(Compress::from_path(path.as_ref()), Some(method));
+        }
+        assert_eq!(Compress::from_path("save.json".as_ref()), None);
+    }
+
+    #[test]
+    fn plain_saves() -> Result<()> {
+        let save = large_save();
+        let data = write_json(None, Vec::new(), &save)?;
+        assert_eq!(Compress::detect(&data), None);
+        let json = decompress(data)?;
+        assert_eq!(serde_json::from_str::<WorldSer>(&json)?, save);
+        Ok(())
+    }
+
+    #[cfg(feature = "compress")]
+    #[test]
+    fn compressed_saves() -> Result<()> {
+        let save = large_save();
+        let plain = serde_json::to_vec(&save)?;
+        for &method in &[Compress::Gzip, Compress::Zstd] {
+            let data = write_json(Some(method), Vec::new(), &save)?;
+            assert_eq!(Compress::detect(&data), Some(method));
+            assert!(data.len() * 4 < plain.len(), "{:?}", method);
+            let json = decompress(data)?;
+            assert_eq!(json.as_bytes(), &plain[..]);
+            assert_eq!(serde_json::from_str::<WorldSer>(&json)?, save);
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "compress"))]
+    #[test]
+    fn compressed_saves() {
+        assert!(require().is_err());
+        assert!(write_json(Some(Compress::Gzip), Vec::new(), &large_save()).is_err());
+        assert!(decompress(GZIP_MAGIC.to_vec()).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+//! Options of a search in a TOML file, read with `--config`.
+//!
+//! The options given on the command line override the ones in the file.
+//! The options of each run are written back to `effective-config.toml` in
+//! the result directory, so that `--config` can run the search again.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{
+    fs::{read_to_string, write},
+    path::{Path, PathBuf},
+};
+
+/// The name of the effective configuration in the result directory.
+pub const EFFECTIVE_CONFIG_NAME: &str = "effective-config.toml";
+
+/// The options that can be given in the file, named as on the command line,
+/// with `_` instead of `-`. Unknown keys are errors.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+    /// A period, or a range of periods as a string, e.g., `"3..=10"`.
+    #[serde(
+        default,
+        deserialize_with = "string_or_integer",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub period: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dx: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dy: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symmetry: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_width: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub init_width: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub init_height: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub init_cell_count: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grow: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_height: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_results: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub view_freq: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub save_freq: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub save_dir: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub save_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_template: Option<String>,
+}
+
+/// Accepts `period = 4` as well as `period = "3..=10"`.
+fn string_or_integer<'de, D: Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrInteger {
+        String(String),
+        Integer(i64),
+    }
+    Ok(Option::<StringOrInteger>::deserialize(d)?.map(|v| match v {
+        StringOrInteger::String(s) => s,
+        StringOrInteger::Integer(n) => n.to_string(),
+    }))
+}
+
+impl ConfigFile {
+    /// Reads a file.
+    pub fn read(path: &Path) -> Result<Self> {
+        toml::from_str(&read_to_string(path)?)
+            .with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// Writes the file.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn periods_as_integers_or_ranges() {
+        let file: ConfigFile = toml::from_str("period = 4").unwrap();
+        assert_eq!(file.period.as_deref(), Some("4"));
+        let file: ConfigFile = toml::from_str("period = \"3..=10\"").unwrap();
+        assert_eq!(file.period.as_deref(), Some("3..=10"));
+        let file: ConfigFile = toml::from_str("").unwrap();
+        assert_eq!(file.period, None);
+    }
+
+    #[test]
+    fn unknown_keys() {
+        assert!(toml::from_str::<ConfigFile>("dx = 1\nmax_widht = 12").is_err());
+        assert!(toml::from_str::<ConfigFile>("dx = \"one\"").is_err());
+    }
+
+    #[test]
+    fn only_the_given_options_are_written() {
+        let file = ConfigFile {
+            period: Some("4".to_owned()),
+            dx: Some(1),
+            rule: Some("B3/S23".to_owned()),
+            ..ConfigFile::default()
+        };
+        let toml = toml::to_string(&file).unwrap();
+        assert_eq!(toml, "period = \"4\"\ndx = 1\nrule = \"B3/S23\"\n");
+        let again: ConfigFile = toml::from_str(&toml).unwrap();
+        assert_eq!(toml::to_string(&again).unwrap(), toml);
+    }
+}
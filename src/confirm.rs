@@ -0,0 +1,145 @@
+//! Confirmation before destroying data.
+
+use anyhow::{bail, Result};
+use std::{
+    io::{stdin, stdout, BufRead, IsTerminal, Write},
+    path::PathBuf,
+};
+
+/// Number of example paths to show in the confirmation.
+const EXAMPLES: usize = 5;
+
+/// Asks the user to confirm an operation that deletes or overwrites `paths`.
+///
+/// Succeeds at once if `yes` is true, i.e., `--yes` is given. Otherwise, in
+/// a terminal, the user has to type `yes`; when the input is not a terminal,
+/// the operation is aborted.
+pub fn confirm_destructive(action: &str, paths: &[PathBuf], yes: bool) -> Result<()> {
+    let interactive = stdin().is_terminal();
+    confirm(
+        action,
+        paths,
+        yes,
+        interactive,
+        &mut stdin().lock(),
+        &mut stdout(),
+    )
+}
+
+/// "1 file" or "n files".
+fn files(n: usize) -> String {
+    if n == 1 {
+        String::from("1 file")
+    } else {
+        format!("{} files", n)
+    }
+}
+
+/// Same as [`confirm_destructive`], with the answer read from `input` and
+/// the question written to `output` if `interactive` is true.
+fn confirm<R: BufRead, W: Write>(
+    action: &str,
+    paths: &[PathBuf],
+    yes: bool,
+    interactive: bool,
+    input: &mut R,
+    output: &mut W,
+) -> Result<()> {
+    if yes || paths.is_empty() {
+        return Ok(());
+    }
+    if !interactive {
+        bail!(
+            "This would {} {}. Use --yes to confirm.",
+            action,
+            files(paths.len())
+        );
+    }
+    writeln!(output, "This will {} {}:", action, files(paths.len()))?;
+    for path in paths.iter().take(EXAMPLES) {
+        writeln!(output, "    {}", path.display())?;
+    }
+    if paths.len() > EXAMPLES {
+        writeln!(output, "    and {} more.", paths.len() - EXAMPLES)?;
+    }
+    writeln!(output, "Type `yes` to continue:")?;
+    output.flush()?;
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    if answer.trim() != "yes" {
+        bail!("Aborted.");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(n: usize) -> Vec<PathBuf> {
+        (0..n)
+            .map(|i| PathBuf::from(format!("save.json.{}", i)))
+            .collect()
+    }
+
+    /// Runs the confirmation with the given answer, and returns the result
+    /// and what is printed.
+    fn run(paths: &[PathBuf], yes: bool, interactive: bool, answer: &str) -> (Result<()>, String) {
+        let mut output = Vec::new();
+        let result = confirm(
+            "delete",
+            paths,
+            yes,
+            interactive,
+            &mut answer.as_bytes(),
+            &mut output,
+        );
+        (result, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn yes_skips_the_question() {
+        for &interactive in &[false, true] {
+            let (result, output) = run(&paths(3), true, interactive, "");
+            assert!(result.is_ok());
+            assert!(output.is_empty());
+        }
+    }
+
+    #[test]
+    fn nothing_to_delete() {
+        let (result, output) = run(&[], false, false, "");
+        assert!(result.is_ok());
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn non_interactive_aborts() {
+        let (result, output) = run(&paths(3), false, false, "yes\n");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "This would delete 3 files. Use --yes to confirm."
+        );
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn interactive_yes() {
+        let (result, output) = run(&paths(7), false, true, "yes\n");
+        assert!(result.is_ok());
+        assert_eq!(
+            output,
+            "This will delete 7 files:\n    save.json.0\n    save.json.1\n    save.json.2\n    \
+             save.json.3\n    save.json.4\n    and 2 more.\nType `yes` to continue:\n"
+        );
+    }
+
+    #[test]
+    fn interactive_other_answers_abort() {
+        for answer in &["no\n", "y\n", "", "yes please\n"] {
+            let (result, output) = run(&paths(1), false, true, answer);
+            assert_eq!(result.unwrap_err().to_string(), "Aborted.");
+            assert!(output.starts_with("This will delete 1 file:\n    save.json.0\n"));
+        }
+    }
+}
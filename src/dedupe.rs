@@ -0,0 +1,243 @@
+//! The results already in the result directory, so that a spaceship found
+//! again, e.g., right after resuming, is not saved again, and the search
+//! can start below the smallest one.
+//!
+//! Each `.rle` file is simulated for a period, and its canonical form and
+//! cell count are recorded if it moves with the velocity of the search, in
+//! any orientation. The names and the comments of the files are not used.
+//! Only rules with two states and without `B0` are supported.
+
+use crate::{apgcode::apgcode, signal::CancelToken, target::Target, verify::phases};
+use anyhow::{bail, Result};
+use rlifesrc_lib::{KnownCell, DEAD};
+use std::{
+    collections::HashSet,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+/// The spaceships of the target in the result directory.
+#[derive(Clone, Debug, Default)]
+pub struct Known {
+    /// Their canonical forms.
+    pub canonical: HashSet<String>,
+    /// The smallest cell count, i.e., the minimum over the phases.
+    pub min_cell_count: Option<u32>,
+}
+
+/// The canonical form and the cell count of the spaceship in an RLE file,
+/// or `None` if it does not have the velocity of the target.
+fn canonical(path: &Path, rule_string: &str, target: &Target) -> Result<Option<(String, u32)>> {
+    let cells = KnownCell::from_rles(read_to_string(path)?.as_str())?;
+    let living: Vec<_> = cells
+        .iter()
+        .filter(|cell| cell.coord.2 == 0 && cell.state != DEAD)
+        .map(|cell| (cell.coord.0, cell.coord.1))
+        .collect();
+    if living.is_empty() {
+        bail!("the pattern is empty");
+    }
+    let (phases, (dx, dy)) = match phases(rule_string, &living, target.period)? {
+        Some(result) => result,
+        None => return Ok(None),
+    };
+    let mut moved = [dx.abs(), dy.abs()];
+    let mut expected = [target.dx.abs(), target.dy.abs()];
+    moved.sort_unstable();
+    expected.sort_unstable();
+    if moved != expected {
+        return Ok(None);
+    }
+    let cell_count = phases
+        .iter()
+        .map(|grid| grid.iter().flatten().filter(|&&alive| alive).count() as u32)
+        .min()
+        .unwrap_or(0);
+    Ok(Some((
+        apgcode(&phases, target.period, !target.is_oscillator()),
+        cell_count,
+    )))
+}
+
+/// The spaceships of the target in the `.rle` files in `dir`.
+///
+/// Files that cannot be read are skipped with a warning, and files of
+/// other spaceships or patterns are ignored. When `cancel` is cancelled,
+/// the spaceships in the files read so far are returned.
+pub fn scan(dir: &Path, rule_string: &str, target: &Target, cancel: &CancelToken) -> Result<Known> {
+    if !dir.is_dir() {
+        return Ok(Known::default());
+    }
+    let mut paths = Vec::new();
+    for entry in dir.read_dir()? {
+        paths.push(entry?.path());
+    }
+    Ok(scan_paths(paths, rule_string, target, cancel))
+}
+
+/// The spaceships of the target in the `.rle` files among `paths`.
+fn scan_paths<I>(paths: I, rule_string: &str, target: &Target, cancel: &CancelToken) -> Known
+where
+    I: IntoIterator<Item = PathBuf>,
+{
+    let mut known = Known::default();
+    for path in paths {
+        if cancel.is_cancelled() {
+            break;
+        }
+        if path.extension().is_none_or(|ext| ext != "rle") {
+            continue;
+        }
+        match canonical(&path, rule_string, target) {
+            Ok(Some((code, cell_count))) => {
+                known.canonical.insert(code);
+                known.min_cell_count = Some(
+                    known
+                        .min_cell_count
+                        .map_or(cell_count, |min| min.min(cell_count)),
+                );
+            }
+            Ok(None) => {}
+            Err(e) => println!("Warning: cannot read {}: {}", path.display(), e),
+        }
+    }
+    known
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        env,
+        fs::{create_dir_all, remove_dir_all, write},
+        process,
+    };
+
+    const GLIDER: Target = Target {
+        period: 4,
+        dx: 1,
+        dy: 1,
+    };
+    const LWSS: Target = Target {
+        period: 4,
+        dx: 2,
+        dy: 0,
+    };
+
+    #[test]
+    fn mixed_and_foreign_files() -> Result<()> {
+        let dir = env::temp_dir().join(format!("spaceships-dedupe-{}", process::id()));
+        create_dir_all(&dir)?;
+        let files = [
+            // The glider, in two phases and two orientations.
+            ("5P4H1V1.rle", "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n"),
+            (
+                "glider.rle",
+                "#C Another phase.\nx = 3, y = 3\nobo$b2o$bo!\n",
+            ),
+            (
+                "upside down.rle",
+                "x = 3, y = 3, rule = B3/S23\n3o$2bo$bo!\n",
+            ),
+            // The LWSS, in two orientations.
+            (
+                "9P4H2V0.rle",
+                "x = 5, y = 4, rule = B3/S23\nbo2bo$o4b$o3bo$4o!\n",
+            ),
+            (
+                "lwss.rle",
+                "#C Upside down.\nx = 5, y = 4\n4o$o3bo$o4b$bo2bo!\n",
+            ),
+            // Not spaceships.
+            ("blinker.rle", "x = 3, y = 1, rule = B3/S23\n3o!\n"),
+            ("block.rle", "x = 2, y = 2, rule = B3/S23\n2o$2o!\n"),
+            // Not results.
+            ("empty.rle", "x = 0, y = 0, rule = B3/S23\n!\n"),
+            ("broken.rle", "x = 3, y = 3\nnot a pattern!\n"),
+            ("notes.txt", "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n"),
+        ];
+        for (name, content) in &files {
+            write(dir.join(name), content)?;
+        }
+
+        let known = scan(&dir, "B3/S23", &GLIDER, &CancelToken::new())?;
+        assert_eq!(known.canonical.len(), 1);
+        assert!(known.canonical.contains("xq4_153"));
+        assert_eq!(known.min_cell_count, Some(5));
+
+        let known = scan(&dir, "B3/S23", &LWSS, &CancelToken::new())?;
+        assert_eq!(known.canonical.len(), 1);
+        assert!(known.canonical.contains("xq4_6frc"));
+        assert_eq!(known.min_cell_count, Some(9));
+
+        // Moving in other directions does not matter.
+        let known = scan(
+            &dir,
+            "B3/S23",
+            &Target {
+                dx: 0,
+                dy: -2,
+                ..LWSS
+            },
+            &CancelToken::new(),
+        )?;
+        assert!(known.canonical.contains("xq4_6frc"));
+
+        let known = scan(
+            &dir,
+            "B3/S23",
+            &Target {
+                period: 3,
+                dx: 1,
+                dy: 0,
+            },
+            &CancelToken::new(),
+        )?;
+        assert!(known.canonical.is_empty());
+        assert_eq!(known.min_cell_count, None);
+
+        // In another rule, the glider does not move.
+        let known = scan(&dir, "B36/S23", &LWSS, &CancelToken::new())?;
+        assert!(!known.canonical.contains("xq4_153"));
+
+        remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn cancelled_scan() -> Result<()> {
+        let dir = env::temp_dir().join(format!("spaceships-dedupe-cancelled-{}", process::id()));
+        create_dir_all(&dir)?;
+        let (mwss, lwss) = (dir.join("11P4H2V0.rle"), dir.join("9P4H2V0.rle"));
+        write(&mwss, "x = 6, y = 5, rule = B3/S23\n3bo$bo3bo$o$o4bo$5o!\n")?;
+        write(&lwss, "x = 5, y = 4, rule = B3/S23\nbo2bo$o4b$o3bo$4o!\n")?;
+        let paths = vec![mwss, lwss.clone()];
+
+        let known = scan_paths(paths.clone(), "B3/S23", &LWSS, &CancelToken::new());
+        assert_eq!(known.canonical.len(), 2);
+        assert_eq!(known.min_cell_count, Some(9));
+
+        // Cancelled when the LWSS is reached, after the MWSS is read.
+        let cancel = CancelToken::new();
+        let paths = paths.into_iter().inspect(|path| {
+            if path == &lwss {
+                cancel.cancel();
+            }
+        });
+        let known = scan_paths(paths, "B3/S23", &LWSS, &cancel);
+        assert_eq!(known.canonical.len(), 1);
+        assert_eq!(known.min_cell_count, Some(11));
+
+        remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn missing_directory() -> Result<()> {
+        let dir = env::temp_dir().join(format!("spaceships-dedupe-missing-{}", process::id()));
+        let known = scan(&dir, "B3/S23", &GLIDER, &CancelToken::new())?;
+        assert!(known.canonical.is_empty());
+        assert_eq!(known.min_cell_count, None);
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+//! A human-readable log of the events during the search.
+//!
+//! Each line starts with an RFC 3339 timestamp in UTC and the name of the
+//! event, followed by a description, e.g.,
+//!
+//! ```text
+//! 2024-05-01T12:00:00Z found 9 cells (xq4_6frc) at width 7, height 6: 9P4H0V2.rle
+//! ```
+//!
+//! Unlike the results log, it records everything that happened, including
+//! the start and the stop of each run and each save. It is written to
+//! `search.log` in the result directory unless `--no-log` is given.
+
+use crate::time;
+use anyhow::Result;
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+/// The default name of the log in the result directory.
+pub const EVENT_LOG_NAME: &str = "search.log";
+
+/// The log file.
+pub struct EventLog {
+    file: File,
+}
+
+impl EventLog {
+    /// Opens the log file in append mode.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(EventLog { file })
+    }
+
+    /// Writes a line to the log file.
+    pub fn write(&self, event: &str, description: &str) -> Result<()> {
+        let line = format!("{} {} {}\n", time::timestamp(), event, description);
+        (&self.file).write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
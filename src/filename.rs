@@ -0,0 +1,235 @@
+//! Choosing filenames for the results.
+
+use anyhow::{bail, Result};
+use std::{
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+/// The pattern in the content of a result file, without comment lines.
+fn pattern_of(content: &str) -> String {
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.starts_with('!'))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Finds a path in `dir` to save the pattern, without overwriting
+/// existing results.
+///
+/// The path is `{stem}.{ext}` if no such file exists; otherwise a suffix
+/// is appended, as in `{stem}_2.{ext}`, `{stem}_3.{ext}`, etc.
+///
+/// Returns `None` if the same pattern is already saved in one of these files.
+/// Comment lines are ignored when comparing the patterns.
+///
+/// On case-insensitive filesystems, `Path::exists` also finds the files whose
+/// names only differ in case, so such files are never overwritten either.
+pub fn unique_path(dir: &Path, stem: &str, ext: &str, pattern: &str) -> Result<Option<PathBuf>> {
+    let pattern = pattern_of(pattern);
+    for i in 1.. {
+        let path = if i == 1 {
+            dir.join(format!("{}.{}", stem, ext))
+        } else {
+            dir.join(format!("{}_{}.{}", stem, i, ext))
+        };
+        if !path.exists() {
+            return Ok(Some(path));
+        }
+        if pattern_of(&read_to_string(&path)?) == pattern {
+            return Ok(None);
+        }
+    }
+    unreachable!()
+}
+
+/// The default filename template.
+pub const DEFAULT_TEMPLATE: &str = "{cells}P{period}H{dx}V{dy}";
+
+/// The filename template used instead of the default one for oscillators.
+pub const OSCILLATOR_TEMPLATE: &str = "{cells}P{period}";
+
+/// Placeholders supported in the filename template.
+pub const PLACEHOLDERS: [&str; 10] = [
+    "cells",
+    "period",
+    "dx",
+    "dy",
+    "height",
+    "width",
+    "rule",
+    "symmetry",
+    "timestamp",
+    "seq",
+];
+
+/// Checks that the filename template only uses supported placeholders.
+pub fn check_template(template: &str) -> Result<()> {
+    let values: Vec<_> = PLACEHOLDERS
+        .iter()
+        .map(|&key| (key, String::new()))
+        .collect();
+    render(template, &values).map(|_| ())
+}
+
+/// Makes a filename valid on all platforms, replacing reserved characters
+/// with `_` and removing trailing dots and spaces.
+pub fn sanitize_filename(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_control() || "<>:\"/\\|?*".contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    name.trim_end_matches(['.', ' ']).to_owned()
+}
+
+/// Makes a rule string safe to be used in a filename,
+/// e.g., `B3/S23` becomes `b3s23`.
+pub fn sanitize_rule(rule: &str) -> String {
+    rule.chars()
+        .filter(|&c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Renders a filename template,
+/// replacing each `{key}` with the corresponding value.
+pub fn render(template: &str, values: &[(&str, String)]) -> Result<String> {
+    let mut name = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        name += &rest[..start];
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => bail!(
+                "Unclosed placeholder in the filename template: {}",
+                template
+            ),
+        };
+        let key = &rest[start + 1..end];
+        match values.iter().find(|(k, _)| *k == key) {
+            Some((_, value)) => name += value,
+            None => bail!("Unknown placeholder in the filename template: {{{}}}", key),
+        }
+        rest = &rest[end + 1..];
+    }
+    name += rest;
+    Ok(name)
+}
+
+/// Replaces each `{key}` in a command with the corresponding value,
+/// keeping the other braces, e.g., of the shell, as they are.
+pub fn substitute(command: &str, values: &[(&str, String)]) -> String {
+    values
+        .iter()
+        .fold(command.to_owned(), |command, (key, value)| {
+            command.replace(&format!("{{{}}}", key), value)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        env,
+        fs::{create_dir_all, remove_dir_all, write},
+        process,
+    };
+
+    const GLIDER: &str = "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n";
+    const BLINKER: &str = "x = 3, y = 1, rule = B3/S23\n3o!\n";
+
+    #[test]
+    fn unique_paths() -> Result<()> {
+        let dir = env::temp_dir().join(format!("spaceships-filename-{}", process::id()));
+        create_dir_all(&dir)?;
+        let first = dir.join("5P4H1V1.rle");
+
+        // A fresh name.
+        assert_eq!(
+            unique_path(&dir, "5P4H1V1", "rle", GLIDER)?,
+            Some(first.clone())
+        );
+        write(&first, format!("#C Found by spaceships.\n{}", GLIDER))?;
+
+        // The same ship twice, even with different comments.
+        assert_eq!(unique_path(&dir, "5P4H1V1", "rle", GLIDER)?, None);
+
+        // A different ship with the same population.
+        let second = dir.join("5P4H1V1_2.rle");
+        assert_eq!(
+            unique_path(&dir, "5P4H1V1", "rle", BLINKER)?,
+            Some(second.clone())
+        );
+        write(&second, BLINKER)?;
+        assert_eq!(unique_path(&dir, "5P4H1V1", "rle", BLINKER)?, None);
+        assert_eq!(
+            unique_path(&dir, "5P4H1V1", "rle", "x = 1, y = 1\no!\n")?,
+            Some(dir.join("5P4H1V1_3.rle"))
+        );
+
+        remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn rules_in_filenames() {
+        assert_eq!(sanitize_rule("B3/S23"), "b3s23");
+        assert_eq!(sanitize_rule("23/3/2"), "2332");
+        assert_eq!(sanitize_rule("B2-a/S12_x"), "b2-as12_x");
+        // Rules that only differ in case never collide on case-insensitive
+        // filesystems, since they give the same name.
+        assert_eq!(sanitize_rule("B3/S23"), sanitize_rule("b3s23"));
+        assert_eq!(sanitize_rule("B3/S23H"), sanitize_rule("b3/s23h"));
+    }
+
+    #[test]
+    fn sanitized_filenames() {
+        assert_eq!(sanitize_filename("5P4H1V1"), "5P4H1V1");
+        assert_eq!(sanitize_filename("B3/S23 5P4"), "B3_S23 5P4");
+        assert_eq!(
+            sanitize_filename("a<b>c:d\"e\\f|g?h*i"),
+            "a_b_c_d_e_f_g_h_i"
+        );
+        assert_eq!(sanitize_filename("tab\there"), "tab_here");
+        assert_eq!(sanitize_filename("ship. . "), "ship");
+    }
+
+    #[test]
+    fn templates() -> Result<()> {
+        let values = [
+            ("cells", "5".to_owned()),
+            ("period", "4".to_owned()),
+            ("dx", "1".to_owned()),
+            ("dy", "1".to_owned()),
+            ("rule", "b3s23".to_owned()),
+        ];
+        assert_eq!(render(DEFAULT_TEMPLATE, &values)?, "5P4H1V1");
+        assert_eq!(render(OSCILLATOR_TEMPLATE, &values)?, "5P4");
+        assert_eq!(render("{rule}-{cells}{cells}", &values)?, "b3s23-55");
+        assert_eq!(render("no placeholders", &values)?, "no placeholders");
+        assert!(render("{symmetry}", &values).is_err());
+        assert!(render("{cells", &values).is_err());
+
+        check_template(DEFAULT_TEMPLATE)?;
+        check_template("{rule}_{symmetry}_{timestamp}_{seq}_{width}x{height}")?;
+        assert!(check_template("{population}").is_err());
+        assert!(check_template("{cells}P{period").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn commands() {
+        let values = [("path", "a.rle".to_owned()), ("cells", "5".to_owned())];
+        assert_eq!(
+            substitute("echo {path} {cells} ${HOME} {other}", &values),
+            "echo a.rle 5 ${HOME} {other}"
+        );
+    }
+}
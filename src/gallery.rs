@@ -0,0 +1,129 @@
+//! The gallery of the results, `index.html` in the result directory.
+//!
+//! The entries are also embedded in the page as JSON, so that the page can
+//! be rebuilt with a new entry without parsing the result files again.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{read_to_string, rename, write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// The name of the gallery in the result directory.
+pub const GALLERY_NAME: &str = "index.html";
+
+/// Marks the start of the embedded entries.
+const DATA_START: &str = "<script type=\"application/json\" id=\"results\">\n";
+
+/// Marks the end of the embedded entries.
+const DATA_END: &str = "\n</script>";
+
+/// Serializes the updates of the gallery by the threads of a search.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// A result in the gallery.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Entry {
+    /// The filename of the result, relative to the result directory.
+    pub file: String,
+    pub cell_count: u32,
+    pub period: i32,
+    pub dx: i32,
+    pub dy: i32,
+    pub symmetry: String,
+    pub rule: String,
+    pub apgcode: Option<String>,
+    /// The pattern in the format of the result file, without comments.
+    pub pattern: String,
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Reads the entries embedded in an existing gallery.
+fn read(path: &Path) -> Result<Vec<Entry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let html = read_to_string(path)?;
+    let data = html
+        .split_once(DATA_START)
+        .and_then(|(_, rest)| rest.split_once(DATA_END))
+        .map_or("[]", |(data, _)| data);
+    Ok(serde_json::from_str(data)?)
+}
+
+/// The entries of the gallery in `dir`.
+pub fn entries(dir: &Path) -> Result<Vec<Entry>> {
+    read(&dir.join(GALLERY_NAME))
+}
+
+fn render(entries: &[Entry]) -> Result<String> {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>Spaceships</title>\n\
+         <style>\n\
+         body { font-family: sans-serif; }\n\
+         pre { background: #f4f4f4; padding: 0.5em; overflow-x: auto; }\n\
+         </style>\n\
+         </head>\n\
+         <body>\n\
+         <h1>Spaceships</h1>\n",
+    );
+    for entry in entries {
+        html += &format!(
+            "<section>\n\
+             <h2><a href=\"{}\">{}</a></h2>\n\
+             <p>{} cells, ({},{})c/{}, symmetry {}, rule {}{}</p>\n\
+             <pre>{}</pre>\n\
+             </section>\n",
+            escape(&entry.file),
+            escape(&entry.file),
+            entry.cell_count,
+            entry.dx,
+            entry.dy,
+            entry.period,
+            escape(&entry.symmetry),
+            escape(&entry.rule),
+            entry
+                .apgcode
+                .as_ref()
+                .map(|a| format!(", apgcode {}", escape(a)))
+                .unwrap_or_default(),
+            escape(entry.pattern.trim_end())
+        );
+    }
+    // `</` is escaped, so that the JSON never ends the script early.
+    let data = serde_json::to_string_pretty(entries)?.replace("</", "<\\/");
+    html += DATA_START;
+    html += &data;
+    html += DATA_END;
+    html += "\n</body>\n</html>\n";
+    Ok(html)
+}
+
+/// Adds a result to the gallery in `dir`, keeping the entries sorted by
+/// the cell count.
+///
+/// Returns the path of the gallery.
+pub fn add(dir: &Path, entry: Entry) -> Result<PathBuf> {
+    let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = dir.join(GALLERY_NAME);
+    let mut entries = read(&path)?;
+    entries.retain(|e| e.file != entry.file);
+    entries.push(entry);
+    entries.sort_by(|a, b| (a.cell_count, &a.file).cmp(&(b.cell_count, &b.file)));
+    let tmp = dir.join(format!("{}.tmp", GALLERY_NAME));
+    write(&tmp, render(&entries)?)?;
+    rename(tmp, &path)?;
+    Ok(path)
+}
@@ -0,0 +1,60 @@
+//! Which dimension of the world grows when no more result can be found.
+
+use std::{fmt, str::FromStr};
+
+/// Which dimension of the world grows when no more result can be found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Grow {
+    /// Grow the height, with a fixed width.
+    Height,
+    /// Grow the width, with a fixed height.
+    Width,
+    /// Grow the smaller one of the width and the height,
+    /// or the height if they are equal.
+    Both,
+}
+
+impl FromStr for Grow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "height" => Ok(Grow::Height),
+            "width" => Ok(Grow::Width),
+            "both" => Ok(Grow::Both),
+            _ => Err(String::from("invalid growth mode")),
+        }
+    }
+}
+
+impl fmt::Display for Grow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Grow::Height => write!(f, "height"),
+            Grow::Width => write!(f, "width"),
+            Grow::Both => write!(f, "both"),
+        }
+    }
+}
+
+impl Grow {
+    /// Whether the width grows next, rather than the height,
+    /// in a world of this size.
+    ///
+    /// A dimension stops growing when it reaches its maximum, if any.
+    pub fn grows_width(
+        self,
+        width: i32,
+        height: i32,
+        max_width: i32,
+        max_height: Option<i32>,
+    ) -> bool {
+        match self {
+            Grow::Height => false,
+            Grow::Width => true,
+            Grow::Both if width >= max_width => false,
+            Grow::Both if max_height.is_some_and(|h| height >= h) => true,
+            Grow::Both => width < height,
+        }
+    }
+}
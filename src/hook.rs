@@ -0,0 +1,89 @@
+//! Commands to run when a spaceship is found.
+//!
+//! This requires the `hooks` feature.
+
+use anyhow::{bail, Result};
+use std::str::FromStr;
+#[cfg(feature = "hooks")]
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    thread,
+};
+
+/// What is written to the standard input of the command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookInput {
+    /// The result in RLE format.
+    Rle,
+}
+
+impl FromStr for HookInput {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rle" => Ok(HookInput::Rle),
+            _ => Err(String::from("invalid input of the command")),
+        }
+    }
+}
+
+/// Fails if this build does not support running commands.
+pub fn require() -> Result<()> {
+    if !cfg!(feature = "hooks") {
+        bail!("This build lacks the `hooks` feature.")
+    }
+    Ok(())
+}
+
+/// Runs the command in the shell with the given environment variables,
+/// without waiting for it. The `input`, if any, is written to its standard
+/// input.
+///
+/// A command that cannot be started, or exits with a nonzero code, only
+/// prints a warning.
+#[cfg(feature = "hooks")]
+pub fn spawn(command: &str, env: Vec<(&'static str, String)>, input: Option<String>) {
+    let shell = if cfg!(windows) { "cmd" } else { "sh" };
+    let flag = if cfg!(windows) { "/C" } else { "-c" };
+    let mut child = match Command::new(shell)
+        .arg(flag)
+        .arg(command)
+        .envs(env)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            println!("Warning: cannot run the command `{}`: {}", command, e);
+            return;
+        }
+    };
+    let command = command.to_owned();
+    thread::spawn(move || {
+        // The input is written here, so that a command that does not read
+        // it never blocks the search. Dropping the pipe closes it.
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            if let Err(e) = stdin.write_all(input.as_bytes()) {
+                println!("Warning: cannot write to the command `{}`: {}", command, e);
+            }
+        }
+        match child.wait() {
+            Ok(status) if status.success() => {}
+            Ok(status) => println!("Warning: the command `{}` exited with {}.", command, status),
+            Err(e) => println!("Warning: cannot wait for the command `{}`: {}", command, e),
+        }
+    });
+}
+
+#[cfg(not(feature = "hooks"))]
+pub fn spawn(command: &str, _: Vec<(&'static str, String)>, _: Option<String>) {
+    if let Err(e) = require() {
+        println!("Warning: cannot run the command `{}`: {}", command, e);
+    }
+}
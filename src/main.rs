@@ -1,22 +1,91 @@
-use ansi_term::{Color, Style};
-use anyhow::Result;
+mod apgcode;
+mod budget;
+mod compress;
+mod config_file;
+mod confirm;
+mod dedupe;
+mod eventlog;
+mod filename;
+mod gallery;
+mod grow;
+mod hook;
+mod manifest;
+mod memory;
+mod output;
+mod pattern;
+mod proctitle;
+mod results;
+mod shared;
+mod sidecar;
+mod signal;
+mod stats;
+mod survey;
+mod svg;
+mod symmetry;
+mod target;
+mod time;
+mod timer;
+mod trace;
+mod trend;
+mod tui;
+mod verify;
+
+use anyhow::{anyhow, bail, Result};
+use apgcode::apgcode;
+use compress::Compress;
+use config_file::{ConfigFile, EFFECTIVE_CONFIG_NAME};
+use confirm::confirm_destructive;
+use eventlog::{EventLog, EVENT_LOG_NAME};
+use filename::{
+    check_template, render, sanitize_filename, sanitize_rule, substitute, unique_path,
+    DEFAULT_TEMPLATE, OSCILLATOR_TEMPLATE, PLACEHOLDERS,
+};
+use grow::Grow;
+use hook::HookInput;
+use manifest::{Category, CleanOpt, RegenerateOpt};
+use output::{ColorChoice, DisplayPhase, Output, View};
+use pattern::{display_char, Format, Pattern};
+use results::LogFormat;
 use rlifesrc_lib::{
-    save::WorldSer, Config, NewState, PolyWorld, State, Status, Symmetry, ALIVE, DEAD,
+    save::WorldSer, Config, KnownCell, NewState, PolyWorld, SearchOrder, Status, Symmetry,
+    Transform, DEAD,
 };
-use serde_json::{from_str, to_vec};
+use serde_json::{from_str, json, Value};
+use shared::Shared;
+use sidecar::Sidecar;
+use signal::CancelToken;
+use stats::{SearchStats, StatsFile};
 use std::{
-    fs::{create_dir_all, File},
-    io::{Read, Write},
+    collections::{BTreeSet, HashSet, VecDeque},
+    fs::{create_dir_all, remove_file, rename, write, File},
+    io::{stdout, Read, Write},
     path::{Path, PathBuf},
+    sync::{Arc, Barrier},
+    thread,
+    time::Duration,
+};
+use structopt::{
+    clap::{AppSettings, ArgMatches},
+    StructOpt,
+};
+use survey::HeightReport;
+use svg::SvgStyle;
+use symmetry::{
+    act_on_vector, canonical_transforms, compatible_symmetries, is_compatible, transform_symmetry,
 };
-use stopwatch::Stopwatch;
-use structopt::StructOpt;
+use target::{read_batch, read_toml_batch, Job, PeriodRange, Target};
 use term_size::dimensions;
+use timer::{Timer, Times};
+use trace::{Event, PlotOpt, Snapshot, Trace};
+use trend::Trend;
+use tui::TuiView;
+use verify::{phases, verify};
 
 #[derive(Clone, Debug, StructOpt)]
 #[structopt(
     no_version,
     author = "AlephAlpha",
+    setting = AppSettings::SubcommandsNegateReqs,
     about = "Search for spaceships in Conway's Game of Life using the rlifesrc lib.\n\
              \n\
              It starts from a given minimum height, and an optional upper bound of \
@@ -29,23 +98,30 @@ use term_size::dimensions;
              continue the search.\n\
              \n\
              Spaceships with period `p`, speed `(x,y)c/p`, and `n` cells are saved \
-             in the file `{n}P{p}H{x}V{y}.rle`.\n\
+             in the file `{n}P{p}H{x}V{y}.rle`, in the orientation of the search \
+             unless `--canonical-output` is given.\n\
              \n\
-             Press `Ctrl-C` to abort."
+             Press `Ctrl-C` to save the search status and abort."
 )]
 struct Opt {
     /// Search results are saved here.
-    #[structopt(short, long)]
+    #[structopt(short, long, default_value = "", hide_default_value = true)]
     dir: PathBuf,
-    /// Period.
-    #[structopt(short, long)]
-    period: i32,
+    /// Period, or a range of periods, e.g., `3..=10`.
+    ///
+    /// With a range, the periods are searched one after another, from the
+    /// smallest, each with its own save file, as in a batch.
+    #[structopt(short, long, required_unless_one = &["batch", "from-config", "config"])]
+    period: Option<PeriodRange>,
+    /// Skip the searches with periods smaller than this, e.g., in a batch.
+    #[structopt(long, value_name = "period")]
+    min_period: Option<i32>,
     /// Horizontal translation.
-    #[structopt(short = "x", long)]
-    dx: i32,
+    #[structopt(short = "x", long, required_unless_one = &["batch", "from-config", "config"])]
+    dx: Option<i32>,
     /// Vertical translation.
-    #[structopt(short = "y", long)]
-    dy: i32,
+    #[structopt(short = "y", long, required_unless_one = &["batch", "from-config", "config"])]
+    dy: Option<i32>,
     /// Symmetry.
     #[structopt(short, long, default_value = "C1")]
     symmetry: Symmetry,
@@ -55,246 +131,3662 @@ struct Opt {
     /// Maximum width.
     #[structopt(short = "w", long, default_value = "1024")]
     max_width: i32,
+    /// Use this rule when resuming, instead of the one in the save file.
+    ///
+    /// The cells set at the current size may not be valid with another
+    /// rule, so the search at this size starts again, keeping the height,
+    /// the bound of the cell count, and the statistics.
+    #[structopt(long, value_name = "rule")]
+    override_rule: Option<String>,
+    /// Use this maximum width when resuming, instead of the width in the
+    /// save file, in the same way as `--override-rule`.
+    #[structopt(long, value_name = "width")]
+    override_max_width: Option<i32>,
     /// Initial upper bound of the cell count.
     ///
     /// It will automatically decrease when a new result is found.
     #[structopt(short = "c", long, default_value = "0")]
     init_cell_count: u32,
+    /// Skip the results with fewer cells than this.
+    ///
+    /// They are neither saved nor logged, and do not reduce the upper bound
+    /// of the cell count, so the search goes on with the same bound: a bound
+    /// reduced below this number would leave nothing to find. Larger results
+    /// reduce the bound as usual. The bound from `--init-cell-count`, if any,
+    /// must be above this number.
+    #[structopt(long)]
+    min_cell_count: Option<u32>,
     /// Initial height.
     ///
     /// It will automatically increase when no more result can be found.
     #[structopt(short = "h", long, default_value = "1")]
     init_height: i32,
+    /// Which dimension grows when no more result can be found: `height`,
+    /// `width`, or `both`.
+    ///
+    /// With `width`, the width starts from `--init-width` and grows up to
+    /// `--max-width`, while the height stays at `--init-height`. With
+    /// `both`, the smaller dimension grows first. The mode is recorded in
+    /// the save file, and is kept when resuming.
+    #[structopt(long, default_value = "height")]
+    grow: Grow,
+    /// Initial width, when the width grows.
+    #[structopt(long, default_value = "1")]
+    init_width: i32,
+    /// With `--grow height`, search every width from `--init-width` to
+    /// `--max-width` at each height, increasing the width by this number,
+    /// before increasing the height.
+    ///
+    /// Narrow searches are much faster, and find many of the spaceships that
+    /// a wider search would find later. 0 searches only at `--max-width`.
+    #[structopt(long, default_value = "0", value_name = "n")]
+    width_step: i32,
+    /// With `--grow height`, also increase the width by 1 each time the
+    /// height has increased this number of times in a row without a result.
+    ///
+    /// The width then starts from `--max-width`, and grows without a limit.
+    #[structopt(long, value_name = "n")]
+    grow_width_after: Option<u32>,
+    /// Skip the results narrower than this width.
+    ///
+    /// rlifesrc cannot exclude narrow patterns from the search, so this
+    /// only saves time when the width grows, by starting from this width.
+    #[structopt(long)]
+    min_width: Option<i32>,
     /// Print the world every this number of steps.
     #[structopt(short = "f", long, default_value = "5000000")]
     view_freq: u64,
+    /// Print the world about every this number of milliseconds instead,
+    /// adjusting the number of steps between views to the search speed.
+    ///
+    /// This overrides `--view-freq`.
+    #[structopt(long, value_name = "ms")]
+    view_interval: Option<u64>,
     /// Save the temporary search status every this number of views.
     #[structopt(long, default_value = "100")]
     save_freq: u64,
     /// Temporary search status are saved here.
     #[structopt(long)]
     save_dir: Option<PathBuf>,
+    /// The name of the save file in the save directory, which may include
+    /// subdirectories.
+    ///
+    /// Searches with different names can share a directory. With several
+    /// targets, the velocity is added, e.g., `save_P4H1V1.json`.
+    #[structopt(long, default_value = "save.json", value_name = "name")]
+    save_name: String,
+    /// Start the search from the known cells in this RLE file.
+    ///
+    /// `o` and `b` are known living and dead cells, while `?` and the cells
+    /// beyond the end of each line are unknown. Further RLE patterns in the
+    /// same file are the known cells of the subsequent generations.
+    #[structopt(long)]
+    seed: Option<PathBuf>,
+    /// Keep this number of backups of the search status.
+    ///
+    /// The backups are named `save.json.1`, `save.json.2`, etc.,
+    /// and are loaded if the latest search status is corrupted. Older
+    /// backups, e.g., from an earlier run with a larger number, are deleted
+    /// when the search starts, after a confirmation.
+    #[structopt(long, default_value = "3")]
+    save_rotate: u32,
+    /// Compress the search status: `gzip` (as `save.json.gz`)
+    /// or `zstd` (as `save.json.zst`).
+    ///
+    /// Compressed save files are always recognized when loading,
+    /// whatever their names are.
+    #[structopt(long)]
+    save_compress: Option<Compress>,
+    /// Start a new search even if there is a save file.
+    ///
+    /// The save file and its backups are deleted, after a confirmation.
+    #[structopt(long)]
+    fresh: bool,
+    /// Search without writing any file.
+    ///
+    /// The results are printed in full instead of being saved, and the
+    /// search status is never saved. An existing save file is still loaded.
+    #[structopt(long, conflicts_with_all = &["fresh", "trace", "event-log", "stats-file", "survey"])]
+    dry_run: bool,
+    /// Do not ask for confirmation before deleting or overwriting files.
+    #[structopt(long)]
+    yes: bool,
+    /// Stop the search after this number of seconds of wall-clock time in
+    /// this run, including the display and the saves.
+    ///
+    /// The search status is saved before exiting.
+    #[structopt(long)]
+    timeout: Option<u64>,
+    /// Stop the search after finding this number of spaceships.
+    #[structopt(long)]
+    max_results: Option<u32>,
+    /// Stop the search after finding the first spaceship.
+    #[structopt(long)]
+    stop_after_first: bool,
+    /// Stop the search when no more result can be found at this height.
+    #[structopt(long)]
+    max_height: Option<i32>,
+    /// Move on to the next height when no result is found at the current
+    /// height in this number of seconds of wall-clock time.
+    ///
+    /// Skipped heights are not exhausted, so some spaceships may be missed.
+    #[structopt(long)]
+    per_height_timeout: Option<u64>,
+    /// Do not write the `#C` comment lines in the result files.
+    #[structopt(long)]
+    no_comments: bool,
+    /// Anchor each result to the top: rotate or reflect it so that it moves
+    /// up, and its leading edge is its first row.
+    ///
+    /// Results are always translated so that their top row and left column
+    /// contain living cells, so results found at different heights line up.
+    /// The original offset in the search world is written as a `#C`
+    /// comment.
+    #[structopt(long, conflicts_with_all = &["canonical-output", "canonicalize"])]
+    align_top: bool,
+    /// Write the results in the canonical orientation, instead of as found.
+    ///
+    /// Each result is rotated or reflected so that it moves by `(dx, dy)`
+    /// with `dx >= dy >= 0`, e.g., a `(-2, 0)` search gives the same files
+    /// as a `(2, 0)` search. The velocity and the symmetry in the comments,
+    /// the filename, the gallery, the results log and `--on-found` follow
+    /// the orientation of the files. The search itself is not changed.
+    ///
+    /// This is not the default, so that the names of the results of other
+    /// directions stay the same as in the existing result directories.
+    #[structopt(long)]
+    canonical_output: bool,
+    /// Write each result in the orientation with the smallest row-major
+    /// bit string of its cells among all 8 orientations, whatever its
+    /// velocity.
+    ///
+    /// The same spaceship found in different orientations is then written
+    /// the same way, and also saved only once for rules with more than two
+    /// states.
+    #[structopt(long, conflicts_with = "canonical-output")]
+    canonicalize: bool,
+    /// Do not print the world while searching.
+    ///
+    /// Only prints a line when a result is found.
+    #[structopt(short, long)]
+    quiet: bool,
+    /// When to use colors: `auto`, `always`, or `never`.
+    ///
+    /// `auto` uses colors only when the output is a terminal, and the
+    /// environment variable `NO_COLOR` is not set.
+    #[structopt(long, default_value = "auto")]
+    color: ColorChoice,
+    /// Overwrite a single status line instead of printing the world
+    /// every view.
+    #[structopt(long, conflicts_with = "quiet")]
+    progress_line: bool,
+    /// Redraw the status line and the world in place, like `top`.
+    ///
+    /// Keys: `q` to save and quit, `p` to pause or resume, `+` and `-` to
+    /// step the displayed generation, `s` to save, `j` and `k` to scroll.
+    /// When the output is not a terminal, the usual display is used.
+    #[structopt(long, conflicts_with_all = &["quiet", "progress-line"])]
+    tui: bool,
+    /// Print all phases of the world side by side, with the cell count of
+    /// each phase above it.
+    ///
+    /// Only one phase is printed if they do not fit in the terminal.
+    #[structopt(long)]
+    show_all_phases: bool,
+    /// Also write every phase of each result, each trimmed to its own
+    /// bounding box.
+    ///
+    /// Phase `k` is the result after `k` generations, so that phase 0 is
+    /// the result, each phase becomes the next one after one generation,
+    /// and phase `p - 1` becomes phase 0 translated by `(dx, dy)`. For the result
+    /// `9P4H0V2.rle`, the phases are written to `9P4H0V2_phases/phase_0.rle`
+    /// to `9P4H0V2_phases/phase_3.rle`.
+    #[structopt(long)]
+    all_phases: bool,
+    /// Which generation to display during the search: `cycle` for the next
+    /// one at each view, `min` for the one with the fewest living cells,
+    /// or `fixed N` for generation `N`.
+    #[structopt(long, default_value = "cycle")]
+    display_phase: DisplayPhase,
+    /// Draw the world with Braille characters, each showing 2x4 cells.
+    ///
+    /// Every living cell is a dot, whatever its state in a Generations
+    /// rule. Unknown cells are blank like dead ones.
+    #[structopt(long)]
+    braille: bool,
+    /// Also draw each result in an SVG file next to the result file.
+    #[structopt(long)]
+    svg: bool,
+    /// Width and height of each cell in the SVG files, in pixels.
+    #[structopt(long, default_value = "10")]
+    svg_cell_size: u32,
+    /// Color of the living cells in the SVG files: `#rgb`, `#rrggbb`, or a
+    /// name like `black`.
+    #[structopt(long, default_value = "#000000")]
+    svg_color: String,
+    /// Color of the background in the SVG files, in the same forms as
+    /// `--svg-color`.
+    #[structopt(long, default_value = "#ffffff")]
+    svg_background: String,
+    /// Also write the configuration of the search in a `.toml` file next
+    /// to each result file.
+    #[structopt(long)]
+    emit_config: bool,
+    /// Search with the configuration in a `.toml` file written by
+    /// `--emit-config`.
+    ///
+    /// The search starts from the world where that result was found,
+    /// with the same rule, velocity, symmetry, search order, new state,
+    /// cell count bound, and known cells, overriding the corresponding
+    /// options.
+    #[structopt(long, conflicts_with = "batch")]
+    from_config: Option<PathBuf>,
+    /// Read the options from this TOML file, e.g., `effective-config.toml`
+    /// of a previous run.
+    ///
+    /// The keys are the long options, with `_` instead of `-`: `dir`,
+    /// `period`, `dx`, `dy`, `symmetry`, `rule`, `max_width`,
+    /// `init_width`, `init_height`, `init_cell_count`, `grow`,
+    /// `max_height`, `timeout`, `max_results`, `view_freq`, `save_freq`,
+    /// `save_dir`, `save_name`, and `name_template`. Unknown keys are
+    /// errors. The options on the command line override the file.
+    #[structopt(long, value_name = "file", conflicts_with = "from-config")]
+    config: Option<PathBuf>,
+    /// Save a spaceship even if it is already in the result directory.
+    ///
+    /// By default, the `.rle` files in the result directory are read when
+    /// the search starts, and the spaceships with the same velocity are
+    /// not saved again when they are found.
+    #[structopt(long)]
+    no_dedupe: bool,
+    /// Do not lower the upper bound of the cell count below the smallest
+    /// spaceship of the same velocity in the result directory.
+    ///
+    /// By default, when the search starts or resumes, the upper bound is
+    /// the tighter one of `--init-cell-count` and the smallest spaceship
+    /// in the `.rle` files of the result directory minus 1.
+    #[structopt(long)]
+    no_bound_from_dir: bool,
+    /// The configuration loaded from `--from-config`.
+    #[structopt(skip)]
+    base_config: Option<Config>,
+    /// Template of the result filenames, without the extension.
+    ///
+    /// Supported placeholders are `{cells}`, `{period}`, `{dx}`, `{dy}`,
+    /// `{height}`, `{width}`, `{rule}`, `{symmetry}`, `{timestamp}`, and
+    /// `{seq}`, where the height and width are of the search world. The
+    /// rule is written in lowercase without slashes, e.g., `b3s23`. The
+    /// timestamp is in UTC, e.g., `20240501T120000Z`, and `seq` counts the
+    /// results saved in this run, from 1. Characters that are invalid in
+    /// filenames are replaced with `_`, and a suffix such as `_2` is
+    /// appended if the file already exists.
+    #[structopt(long, visible_alias = "filename-template", default_value = DEFAULT_TEMPLATE)]
+    name_template: String,
+    /// Format of the result files: `rle`, `cells` (or `plaintext`), or
+    /// `life106` (or `l106`).
+    ///
+    /// For Generations rules, Plaintext writes the dying cells as living
+    /// cells, and Life 1.06 only records the living cells.
+    #[structopt(long, default_value = "rle")]
+    format: Format,
+    /// Show more information in the status line.
+    #[structopt(short, long)]
+    verbose: bool,
+    /// Show the number of backtracks, i.e., conflicts, at the current size
+    /// in the status line, and print it when the size changes.
+    #[structopt(long)]
+    track_backtracks: bool,
+    /// Append a JSON line to this file for each event during the search.
+    ///
+    /// Events are the start or resume of the search, new results,
+    /// reductions of the cell count bound, and increases of the height.
+    /// Use `spaceships plot --trace <file>` to convert it to CSV.
+    #[structopt(long)]
+    trace: Option<PathBuf>,
+    /// Append a timestamped, human-readable line to this file for each
+    /// event during the search [default: search.log in the result directory].
+    ///
+    /// Events are the start, resume, and stop of the search, new results,
+    /// increases of the height or the width, and saves.
+    #[structopt(long = "log", value_name = "path")]
+    event_log: Option<PathBuf>,
+    /// Do not write the log of the events.
+    #[structopt(long, conflicts_with = "event-log")]
+    no_log: bool,
+    /// Append a CSV line to this file at each view, with the time, the
+    /// number of steps, the height, and the cell count bound.
+    #[structopt(long)]
+    stats_file: Option<PathBuf>,
+    /// Append each new result to this file.
+    ///
+    /// Defaults to `results.jsonl` or `results.csv` in the result directory.
+    #[structopt(long)]
+    log_file: Option<PathBuf>,
+    /// Format of the results log: `jsonl` or `csv`.
+    #[structopt(long, default_value = "jsonl")]
+    log_format: LogFormat,
+    /// Search order: `auto`, `row`, `col` (or `column`), or `diag`
+    /// (or `diagonal`).
+    ///
+    /// `auto` chooses it from the size of the world. `diag` requires the
+    /// world to be square, so the size cannot grow.
+    #[structopt(
+        long,
+        visible_alias = "order",
+        default_value = "auto",
+        parse(try_from_str = parse_search_order)
+    )]
+    search_order: Order,
+    /// Which state to choose first for an unknown cell: `dead`, `alive`,
+    /// or `random`.
+    ///
+    /// For rules with `B0`, `dead` and `alive` mean the background state and
+    /// its opposite.
+    #[structopt(long, default_value = "dead", parse(try_from_str = parse_new_state))]
+    new_state: NewState,
+    /// Search for the periods and translations listed in this file, one after
+    /// another, instead of the ones given by `-p`, `-x`, and `-y`.
+    ///
+    /// Each line contains a period and a translation, e.g., `4 1 1`,
+    /// optionally followed by a symmetry. Lines starting with `#` are
+    /// ignored. Each target has its own save file,
+    /// e.g., `save_P4H1V1.json`. `--timeout` and `--max-results` apply to
+    /// each target separately.
+    ///
+    /// A `.toml` file instead contains an array of tables named `search`,
+    /// each with `period`, `dx`, `dy`, and optionally `name`, `symmetry`,
+    /// `rule`, `max_width`, `init_cell_count`, `init_height`, `view_freq`,
+    /// `save_freq`, `timeout`, `max_results`, and `max_height`. The same
+    /// fields at the top level are the defaults. The results of each search
+    /// are saved in a subdirectory named by `name`, or e.g. `P4H1V1_C1`.
+    #[structopt(long, visible_alias = "targets")]
+    batch: Option<PathBuf>,
+    /// Search the targets of a batch in turns, each for this number of
+    /// views at a time, instead of one after another.
+    ///
+    /// The search status of each target is saved at the end of its turn.
+    /// `--timeout` then counts from the start of the whole batch.
+    #[structopt(long)]
+    round_robin: Option<u64>,
+    /// Search with every symmetry compatible with the translation,
+    /// one after another, instead of the one given by `--symmetry`.
+    ///
+    /// The symmetry is appended to the names of the result files and the
+    /// save files, e.g., `5P4H1V1_C1.rle`. Symmetries that require a square
+    /// world are skipped, since the height grows during the search.
+    #[structopt(long)]
+    all_symmetries: bool,
+    /// Search with each of these symmetries, one after another, e.g.,
+    /// `C1,C2,D2-`.
+    ///
+    /// The results of each symmetry are saved in its own subdirectory, e.g.,
+    /// `P4H1V1_C2`. Each search starts with the upper bound of the cell
+    /// count reached by the previous one, so that only smaller spaceships
+    /// are found.
+    #[structopt(long, use_delimiter = true, conflicts_with = "all-symmetries")]
+    symmetries: Option<Vec<Symmetry>>,
+    /// Show the times in UTC instead of the local time zone.
+    #[structopt(long)]
+    utc: bool,
+    /// Find all spaceships within the initial upper bound of the cell count,
+    /// instead of reducing the bound after each result.
+    ///
+    /// The bound given by `--init-cell-count` stays fixed, and the height
+    /// only increases when no more result can be found at the current height.
+    ///
+    /// The search backtracks from each result, so it never finds the same
+    /// pattern twice. Other phases and positions of a ship already saved in
+    /// this run are not saved again.
+    #[structopt(long)]
+    no_reduce: bool,
+    /// Stop the search when the memory usage exceeds this size,
+    /// e.g., `8G`.
+    ///
+    /// The memory usage is checked after each view. The search status is
+    /// saved before exiting with code 3, so that the search can be resumed.
+    #[structopt(long, parse(try_from_str = memory::parse_size))]
+    max_memory: Option<u64>,
+    /// Stop after this wall-clock time in this run, e.g., `23h30m`, for
+    /// batch schedulers.
+    ///
+    /// The time counts from the start of the program, over all searches of
+    /// a batch, and is not saved. The search status is saved before exiting
+    /// with code 10, so that the job can be resubmitted to resume it.
+    #[structopt(long, value_name = "duration", parse(try_from_str = time::parse_duration))]
+    max_time: Option<Duration>,
+    /// Stop after this number of search steps in this run, in the same way
+    /// as `--max-time`.
+    ///
+    /// The steps are counted in chunks of up to a million, and a chunk that
+    /// ends with a result or a new size counts in full, so the count is
+    /// approximate.
+    #[structopt(long, value_name = "n")]
+    max_steps: Option<u64>,
+    /// Do not show the progress of the search in the process title.
+    ///
+    /// The title is like `spaceships B3/S23 p4 (0,1) h14 <=57 found:3`.
+    /// On Linux only the height, the bound, and the number of results are
+    /// shown, in `top` and `ps -o comm`.
+    #[structopt(long)]
+    no_proctitle: bool,
+    /// Check each result by simulating it for one period.
+    ///
+    /// A warning is printed if it does not become itself translated by
+    /// the given translation. The result is saved anyway. Rules with `B0`
+    /// cannot be verified.
+    #[structopt(long)]
+    verify: bool,
+    /// Run this shell command after each result is saved, e.g., to send a
+    /// notification.
+    ///
+    /// The placeholders of `--name-template`, e.g., `{cells}`, are replaced
+    /// with the values of the result. The command runs in the background
+    /// with the environment variables
+    /// `SSS_FILE`, `SSS_CELLS`, `SSS_APGCODE`, `SSS_PERIOD`, `SSS_DX`,
+    /// `SSS_DY`, `SSS_RULE`, and `SSS_ELAPSED_SECS`. If it fails, only a
+    /// warning is printed.
+    #[structopt(long, visible_alias = "notification-command", value_name = "command")]
+    on_found: Option<String>,
+    /// Write the result to the standard input of the `--on-found` command.
+    ///
+    /// The only supported value is `rle`.
+    #[structopt(long, requires = "on-found", possible_values = &["rle"])]
+    on_found_stdin: Option<HookInput>,
+    /// Skip the results whose smallest period is a proper divisor of the
+    /// period, e.g., a period 4 glider in a period 8 search.
+    ///
+    /// The upper bound of the cell count is still reduced. The number of
+    /// skipped results is shown in the status line.
+    #[structopt(long)]
+    exact_period: bool,
+    /// Search each height in `--heights` from scratch for this number of
+    /// seconds, and rank the heights by how promising they look.
+    ///
+    /// The ranking is printed and written to `survey.json` in the result
+    /// directory. Results found meanwhile are saved as usual.
+    #[structopt(long, requires = "heights")]
+    survey: Option<u64>,
+    /// The heights to survey, e.g., `6..20`, including both ends.
+    #[structopt(long, parse(try_from_str = survey::parse_heights))]
+    heights: Option<(i32, i32)>,
+    /// Number of threads, each searching at a different height.
+    ///
+    /// When a thread finds a spaceship, the upper bound of the cell count is
+    /// shared with the other threads. When a thread exhausts its height, it
+    /// moves on to the smallest height that no thread has searched yet.
+    /// Each thread has its own save file, e.g., `save_T1.json`, except that
+    /// the first thread uses the usual one.
+    #[structopt(long, visible_alias = "jobs", default_value = "1")]
+    threads: usize,
+    /// The thread of this search in a parallel search.
+    #[structopt(skip)]
+    thread: Option<usize>,
+    /// The result directory given on the command line, when `dir` is a
+    /// subdirectory of it for a search in a batch.
+    #[structopt(skip)]
+    top_dir: Option<PathBuf>,
+    /// A command on the files of earlier searches, instead of a search.
+    #[structopt(subcommand)]
+    command: Option<Subcommand>,
 }
 
-impl Opt {
-    fn sss(&self) -> Result<Sss> {
-        let cell_count = self.init_cell_count;
-        let config = Config::new(self.max_width, self.init_height, self.period)
-            .set_translate(self.dx, self.dy)
-            .set_symmetry(self.symmetry)
-            .set_rule_string(self.rule.clone())
-            .set_new_state(NewState::ChooseDead)
-            .set_max_cell_count(if cell_count > 0 {
-                Some(cell_count - 1)
-            } else {
-                None
-            })
-            .set_reduce_max(true);
-        let gen = 0;
-        let world = config.world()?;
-        let stopwatch = Stopwatch::start_new();
-        Ok(Sss {
-            cell_count,
-            gen,
-            world,
-            stopwatch,
-        })
+/// The commands on the files of earlier searches.
+#[derive(Clone, Debug, StructOpt)]
+enum Subcommand {
+    /// Convert a trace file into a table for plotting.
+    Plot(PlotOpt),
+    /// Delete files listed in the manifest of a result directory.
+    Clean(CleanOpt),
+    /// Rebuild the files deleted by `clean --derived` from the results.
+    Regenerate(RegenerateOpt),
+}
+
+/// The search order, where `None` means automatic.
+///
+/// This alias keeps structopt from treating `--search-order` as an optional
+/// argument, so that `auto` can be parsed into `None`.
+type Order = Option<SearchOrder>;
+
+/// Parses the search order from the command line.
+fn parse_search_order(s: &str) -> Result<Order, String> {
+    match s {
+        "auto" => Ok(None),
+        "row" => Ok(Some(SearchOrder::RowFirst)),
+        "col" | "column" => Ok(Some(SearchOrder::ColumnFirst)),
+        "diag" | "diagonal" => Ok(Some(SearchOrder::Diagonal)),
+        _ => Err(String::from("invalid search order")),
     }
 }
 
-/// Spaceship Search
-struct Sss {
-    cell_count: u32,
-    gen: i32,
-    world: PolyWorld,
-    stopwatch: Stopwatch,
+/// The name of the search order, as on the command line.
+fn search_order_name(order: &Order) -> &'static str {
+    match order {
+        None => "auto",
+        Some(SearchOrder::RowFirst) => "row",
+        Some(SearchOrder::ColumnFirst) => "col",
+        Some(SearchOrder::Diagonal) => "diag",
+        Some(_) => "custom",
+    }
 }
 
-impl Sss {
-    fn from_save<P: AsRef<Path>>(save: P) -> Result<Self> {
-        let mut buffer = String::new();
-        File::open(&save)?.read_to_string(&mut buffer)?;
-        let world = from_str::<WorldSer>(&buffer)?.world()?;
-        let cell_count = world.config().max_cell_count.map(|i| i + 1).unwrap_or(0);
-        let gen = 0;
-        let stopwatch = Stopwatch::start_new();
-        Ok(Sss {
-            cell_count,
-            gen,
-            world,
-            stopwatch,
-        })
+/// Parses the choice of the new state from the command line.
+fn parse_new_state(s: &str) -> Result<NewState, String> {
+    match s {
+        "dead" => Ok(NewState::ChooseDead),
+        "alive" => Ok(NewState::ChooseAlive),
+        "random" => Ok(NewState::Random),
+        _ => Err(String::from("invalid new state")),
     }
+}
 
-    fn display(&self, term_width: usize, style: Style) {
-        let info = format!(
-            "{:=<1$}",
-            format!(
-                "=GEN:{}==HEIGHT:{}==CELLS:{}==TIME:{:.2?}",
-                self.gen,
-                self.world.config().height,
-                self.cell_count,
-                self.stopwatch.elapsed()
-            ),
-            term_width - 1
-        );
-        println!("{}", Color::Yellow.paint(info));
-        let width = (self.world.config().width).min(term_width as i32 - 1);
-        let mut display = String::new();
-        for y in 0..self.world.config().height {
-            for x in 0..width {
-                let state = self.world.get_cell_state((x, y, self.gen));
-                match state {
-                    Some(DEAD) => display.push('.'),
-                    Some(ALIVE) => {
-                        if self.world.is_gen_rule() {
-                            display.push('A')
-                        } else {
-                            display.push('o')
-                        }
-                    }
-                    Some(State(i)) => display.push((b'A' + i as u8 - 1) as char),
-                    None => display.push('?'),
-                };
-            }
-            display.push('\n');
+/// The name of the choice of the new state, as on the command line.
+fn new_state_name(new_state: NewState) -> &'static str {
+    match new_state {
+        NewState::ChooseDead => "dead",
+        NewState::ChooseAlive => "alive",
+        NewState::Random => "random",
+    }
+}
+
+impl Opt {
+    /// The path of the results log.
+    fn log_path(&self) -> PathBuf {
+        match &self.log_file {
+            Some(path) => path.clone(),
+            None => self.dir.join(self.log_format.default_name()),
         }
-        print!("{}", style.paint(display));
     }
 
-    fn write_pat<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
-        let filename = dir.as_ref().join(&format!(
-            "{}P{}H{}V{}.rle",
-            self.cell_count,
-            self.world.config().period,
-            self.world.config().dx,
-            self.world.config().dy
-        ));
-        let mut file = File::create(filename)?;
-        let mut unrle = String::new();
-        let height = self.world.config().height;
-        let mut width = 0;
-        for y in 0..height {
-            let mut line = String::new();
-            for x in 0..self.world.config().width {
-                let state = self.world.get_cell_state((x, y, self.gen));
-                match state {
-                    Some(DEAD) => {
-                        if self.world.is_gen_rule() {
-                            line.push('.')
-                        } else {
-                            line.push('b')
-                        }
-                    }
-                    Some(ALIVE) => {
-                        if self.world.is_gen_rule() {
-                            line.push('A')
-                        } else {
-                            line.push('o')
-                        }
-                    }
-                    Some(State(i)) => line.push((b'A' + i as u8 - 1) as char),
-                    None => line.push('?'),
-                };
+    /// The path of the log of the events, if any.
+    fn event_log_path(&self) -> Option<PathBuf> {
+        if self.no_log || self.dry_run {
+            return None;
+        }
+        match &self.event_log {
+            Some(path) => Some(path.clone()),
+            None => Some(self.dir.join(EVENT_LOG_NAME)),
+        }
+    }
+
+    /// The searches to run, from the batch file or the command line.
+    fn jobs(&self) -> Result<Vec<Job>> {
+        match &self.batch {
+            Some(batch) if batch.extension().is_some_and(|ext| ext == "toml") => {
+                read_toml_batch(batch, self.symmetry)
             }
-            line = line.trim_end_matches(|c| ".b?".contains(c)).to_owned();
-            width = width.max(line.len() as isize);
-            line.push('$');
-            unrle.push_str(&line);
-        }
-        unrle = unrle.trim_end_matches('$').to_owned();
-        unrle.push('!');
-        writeln!(
-            file,
-            "x = {}, y = {}, rule = {}",
-            width,
-            height,
-            self.world.config().rule_string
-        )?;
-        let mut line = String::new();
-        let mut chars = unrle.chars().peekable();
-        let mut count = 0;
-        while let Some(c) = chars.next() {
-            count += 1;
-            if Some(&c) != chars.peek() {
-                let mut run = if count > 1 {
-                    count.to_string()
-                } else {
-                    String::new()
-                };
-                run.push(c);
-                if line.len() + run.len() <= 70 {
-                    line += &run;
+            Some(batch) => read_batch(batch),
+            None => Ok(self
+                .period
+                .unwrap()
+                .periods()
+                .map(|period| Job {
+                    target: Target {
+                        period,
+                        dx: self.dx.unwrap(),
+                        dy: self.dy.unwrap(),
+                    },
+                    subdir: None,
+                    params: Default::default(),
+                })
+                .collect()),
+        }
+    }
+
+    /// Removes the jobs with periods smaller than `--min-period`.
+    fn skip_small_periods(&self, jobs: Vec<Job>) -> Vec<Job> {
+        let min_period = match self.min_period {
+            Some(min_period) => min_period,
+            None => return jobs,
+        };
+        jobs.into_iter()
+            .filter(|job| {
+                let keep = job.target.period >= min_period;
+                if !keep {
+                    println!(
+                        "Warning: skipping {}, whose period is smaller than {}.",
+                        job.target, min_period
+                    );
+                }
+                keep
+            })
+            .collect()
+    }
+
+    /// Whether several targets are searched, from a batch file or a range
+    /// of periods, so that each has its own save file.
+    fn several_targets(&self) -> bool {
+        self.batch.is_some() || self.period.is_some_and(PeriodRange::is_range)
+    }
+
+    /// Replaces each job with one job for each compatible symmetry.
+    fn expand_symmetries(&self, jobs: Vec<Job>) -> Vec<Job> {
+        let mut expanded = Vec::new();
+        for job in jobs {
+            for &symmetry in Symmetry::ALL.iter() {
+                let Target { dx, dy, .. } = job.target;
+                if !is_compatible(symmetry, dx, dy) {
+                    println!(
+                        "Warning: skipping symmetry {} for {}, \
+                         which is incompatible with the translation.",
+                        symmetry, job.target
+                    );
+                } else if symmetry.require_square_world() {
+                    println!(
+                        "Warning: skipping symmetry {} for {}, \
+                         which requires a square world.",
+                        symmetry, job.target
+                    );
                 } else {
-                    writeln!(file, "{}", line)?;
-                    line = run;
+                    let mut job = job.clone();
+                    job.params.symmetry = Some(symmetry);
+                    expanded.push(job);
                 }
-                count = 0;
             }
         }
-        if line.len() < 70 {
-            write!(file, "{}", line)?;
-        } else {
-            writeln!(file, "{}", line)?;
+        expanded
+    }
+
+    /// Replaces each job with one job for each given symmetry,
+    /// in its own subdirectory.
+    fn with_symmetries(&self, jobs: Vec<Job>, symmetries: &[Symmetry]) -> Vec<Job> {
+        let mut expanded = Vec::new();
+        for job in jobs {
+            for &symmetry in symmetries {
+                let mut job = job.clone();
+                let name = job.target.subdir_name(symmetry);
+                job.subdir = Some(match &job.subdir {
+                    Some(subdir) => Path::new(subdir).join(name).to_string_lossy().into_owned(),
+                    None => name,
+                });
+                job.params.symmetry = Some(symmetry);
+                expanded.push(job);
+            }
+        }
+        expanded
+    }
+
+    /// The directory of the manifest, i.e., the result directory given on
+    /// the command line.
+    fn manifest_dir(&self) -> &Path {
+        self.top_dir.as_ref().unwrap_or(&self.dir)
+    }
+
+    /// The options for a search in a batch.
+    fn for_job(&self, job: &Job) -> Opt {
+        let mut opt = self.clone();
+        if let Some(subdir) = &job.subdir {
+            opt.top_dir = Some(self.dir.clone());
+            opt.dir = self.dir.join(subdir);
+            opt.save_dir = self.save_dir.as_ref().map(|dir| dir.join(subdir));
+        }
+        let params = job.params.clone();
+        opt.symmetry = params.symmetry.unwrap_or(opt.symmetry);
+        opt.rule = params.rule.unwrap_or(opt.rule);
+        opt.max_width = params.max_width.unwrap_or(opt.max_width);
+        opt.init_cell_count = params.init_cell_count.unwrap_or(opt.init_cell_count);
+        opt.init_height = params.init_height.unwrap_or(opt.init_height);
+        opt.view_freq = params.view_freq.unwrap_or(opt.view_freq);
+        opt.save_freq = params.save_freq.unwrap_or(opt.save_freq);
+        opt.timeout = params.timeout.or(opt.timeout);
+        opt.max_results = params.max_results.or(opt.max_results);
+        opt.max_height = params.max_height.or(opt.max_height);
+        if opt.all_symmetries && !opt.name_template.contains("{symmetry}") {
+            opt.name_template += "_{symmetry}";
+        }
+        opt
+    }
+
+    /// The options for a thread in a parallel search,
+    /// which starts at its own height.
+    fn for_thread(&self, thread: usize) -> Opt {
+        let mut opt = self.clone();
+        opt.thread = Some(thread);
+        opt.init_height += thread as i32;
+        opt.fresh = false;
+        opt
+    }
+
+    /// Fills the options that are not given on the command line from
+    /// `--config`, if any.
+    fn read_config_file(&mut self, matches: &ArgMatches) -> Result<()> {
+        let path = match &self.config {
+            Some(path) => path.clone(),
+            None => return Ok(()),
+        };
+        let file = ConfigFile::read(&path)?;
+        let absent = |name: &str| matches.occurrences_of(name) == 0;
+        let invalid =
+            |key: &str, e: String| anyhow!("Invalid {} in {}: {}", key, path.display(), e);
+        if let Some(dir) = file.dir.filter(|_| absent("dir")) {
+            self.dir = dir;
+        }
+        if let Some(period) = file.period.filter(|_| absent("period")) {
+            self.period = Some(period.parse().map_err(|e| invalid("period", e))?);
+        }
+        if let Some(dx) = file.dx.filter(|_| absent("dx")) {
+            self.dx = Some(dx);
+        }
+        if let Some(dy) = file.dy.filter(|_| absent("dy")) {
+            self.dy = Some(dy);
+        }
+        if let Some(symmetry) = file.symmetry.filter(|_| absent("symmetry")) {
+            self.symmetry = symmetry
+                .parse()
+                .map_err(|_| invalid("symmetry", symmetry))?;
+        }
+        if let Some(rule) = file.rule.filter(|_| absent("rule")) {
+            self.rule = rule;
+        }
+        if let Some(max_width) = file.max_width.filter(|_| absent("max-width")) {
+            self.max_width = max_width;
+        }
+        if let Some(init_width) = file.init_width.filter(|_| absent("init-width")) {
+            self.init_width = init_width;
+        }
+        if let Some(init_height) = file.init_height.filter(|_| absent("init-height")) {
+            self.init_height = init_height;
+        }
+        if let Some(count) = file.init_cell_count.filter(|_| absent("init-cell-count")) {
+            self.init_cell_count = count;
+        }
+        if let Some(grow) = file.grow.filter(|_| absent("grow")) {
+            self.grow = grow.parse().map_err(|e| invalid("grow", e))?;
+        }
+        if let Some(max_height) = file.max_height.filter(|_| absent("max-height")) {
+            self.max_height = Some(max_height);
+        }
+        if let Some(timeout) = file.timeout.filter(|_| absent("timeout")) {
+            self.timeout = Some(timeout);
+        }
+        if let Some(max_results) = file.max_results.filter(|_| absent("max-results")) {
+            self.max_results = Some(max_results);
+        }
+        if let Some(view_freq) = file.view_freq.filter(|_| absent("view-freq")) {
+            self.view_freq = view_freq;
+        }
+        if let Some(save_freq) = file.save_freq.filter(|_| absent("save-freq")) {
+            self.save_freq = save_freq;
+        }
+        if let Some(save_dir) = file.save_dir.filter(|_| absent("save-dir")) {
+            self.save_dir = Some(save_dir);
+        }
+        if let Some(save_name) = file.save_name.filter(|_| absent("save-name")) {
+            self.save_name = save_name;
+        }
+        if let Some(template) = file.name_template.filter(|_| absent("name-template")) {
+            self.name_template = template;
         }
         Ok(())
     }
 
-    fn write_save<P: AsRef<Path>>(&self, save: P) -> Result<()> {
-        let mut file = File::create(save)?;
-        let json = to_vec(&self.world.ser())?;
-        file.write_all(&json)?;
+    /// Fails if this build lacks a feature that the options require.
+    fn require_features(&self) -> Result<()> {
+        if self.save_compress.is_some() {
+            compress::require()?;
+        }
+        if self.tui {
+            tui::require()?;
+        }
+        if self.svg {
+            svg::require()?;
+        }
+        if self.on_found.is_some() {
+            hook::require()?;
+        }
         Ok(())
     }
 
-    fn search<P: AsRef<Path>, Q: AsRef<Path>>(
-        &mut self,
-        term_width: usize,
-        dir: P,
-        save: Q,
-        view_freq: u64,
-        save_freq: u64,
-    ) -> Result<()> {
-        loop {
-            for _ in 0..save_freq {
-                let status = self.world.search(Some(view_freq));
-                match status {
-                    Status::Found => {
-                        let (min_gen, min_cell_count) = (0..self.world.config().period)
-                            .map(|t| (t, self.world.cell_count_gen(t)))
-                            .min_by_key(|p| p.1)
-                            .unwrap();
-                        self.gen = min_gen;
-                        self.cell_count = min_cell_count;
-                        self.display(term_width, Style::default());
-                        self.write_pat(&dir)?;
-                        self.world.set_max_cell_count(Some(self.cell_count - 1));
-                        self.gen = 0;
-                    }
-                    Status::None => {
-                        let mut config = self.world.config().clone();
-                        config.height += 1;
-                        self.world = config.world()?;
-                        self.gen = 0;
-                    }
-                    Status::Initial | Status::Searching => {
-                        self.display(term_width, Color::Green.normal());
-                        self.gen = (self.gen + 1) % self.world.config().period;
-                    }
-                }
-            }
-            self.write_save(&save)?;
+    /// Checks the options that are required either on the command line,
+    /// or in `--config`.
+    fn check_required(&self) -> Result<()> {
+        if self.dir.as_os_str().is_empty() {
+            bail!("The result directory is required, with --dir or in --config.");
+        }
+        if self.batch.is_none() && (self.period.is_none() || self.dx.is_none() || self.dy.is_none())
+        {
+            bail!("The period and the translation are required, with --period, --dx, and --dy, or in --config.");
         }
+        Ok(())
     }
-}
 
-fn main() -> Result<()> {
-    let term_width = dimensions().unwrap_or((80, 24)).0;
-    let opt = Opt::from_args();
-    create_dir_all(&opt.dir)?;
-    let save_dir = opt.save_dir.as_ref().unwrap_or(&opt.dir);
-    create_dir_all(&save_dir)?;
-    let save = save_dir.join(&"save.json");
-    let mut sss = Sss::from_save(&save).or_else(|_| opt.sss())?;
-    sss.search(term_width, &opt.dir, &save, opt.view_freq, opt.save_freq)
+    /// The options in the format of `--config`.
+    fn effective_config(&self) -> ConfigFile {
+        ConfigFile {
+            dir: Some(self.dir.clone()),
+            period: self.period.map(|p| p.to_string()),
+            dx: self.dx,
+            dy: self.dy,
+            symmetry: Some(self.symmetry.to_string()),
+            rule: Some(self.rule.clone()),
+            max_width: Some(self.max_width),
+            init_width: Some(self.init_width),
+            init_height: Some(self.init_height),
+            init_cell_count: Some(self.init_cell_count),
+            grow: Some(self.grow.to_string()),
+            max_height: self.max_height,
+            timeout: self.timeout,
+            max_results: self.max_results,
+            view_freq: Some(self.view_freq),
+            save_freq: Some(self.save_freq),
+            save_dir: self.save_dir.clone(),
+            save_name: Some(self.save_name.clone()),
+            name_template: Some(self.name_template.clone()),
+        }
+    }
+
+    /// Writes the options to `effective-config.toml` in the result
+    /// directory, so that `--config` can run the search again.
+    fn write_effective_config(&self) -> Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        create_dir_all(&self.dir)?;
+        let path = self.dir.join(EFFECTIVE_CONFIG_NAME);
+        self.effective_config().write(&path)?;
+        manifest::register(&self.dir, &path, Category::Derived, false)
+    }
+
+    /// Loads the configuration of `--from-config`, if any, into the options.
+    fn load_config(&mut self) -> Result<()> {
+        let path = match &self.from_config {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let config = Sidecar::read(path)?.config;
+        self.period = Some(PeriodRange::single(config.period));
+        self.dx = Some(config.dx);
+        self.dy = Some(config.dy);
+        self.symmetry = config.symmetry;
+        self.rule = config.rule_string.clone();
+        self.max_width = config.width;
+        self.init_width = config.width;
+        self.init_height = config.height;
+        self.init_cell_count = config.max_cell_count.map_or(0, |c| c + 1);
+        self.no_reduce = !config.reduce_max;
+        self.search_order = config.search_order.clone();
+        self.new_state = config.new_state;
+        self.base_config = Some(config);
+        Ok(())
+    }
+
+    /// The width of the world at the start of the search.
+    fn start_width(&self) -> i32 {
+        match self.grow {
+            Grow::Height if self.width_step == 0 => self.max_width,
+            Grow::Height => self.init_width.max(self.min_width.unwrap_or(0)),
+            Grow::Width | Grow::Both => self.init_width.max(self.min_width.unwrap_or(0)),
+        }
+    }
+
+    /// Checks the options before searching for a target.
+    fn validate(&self, target: &Target) -> Result<()> {
+        target.check_speed()?;
+        if self.threads == 0 {
+            bail!("The number of threads must be positive.");
+        }
+        if let Some(min) = self.min_cell_count {
+            if self.init_cell_count > 0 && self.init_cell_count <= min {
+                bail!(
+                    "The initial upper bound of the cell count must be larger \
+                     than the minimum cell count {}.",
+                    min
+                );
+            }
+        }
+        if self.svg {
+            svg::check_color("--svg-color", &self.svg_color)?;
+            svg::check_color("--svg-background", &self.svg_background)?;
+        }
+        if let Some(n) = self.grow_width_after {
+            if n == 0 {
+                bail!("The number of heights before growing the width must be positive.");
+            }
+            if self.grow != Grow::Height {
+                bail!("--grow-width-after only works with --grow height.");
+            }
+        }
+        if self.save_name.is_empty() || self.save_name.ends_with(['/', '\\']) {
+            bail!("The name of the save file must not be empty.");
+        }
+        if self.width_step < 0 {
+            bail!("The width step must not be negative.");
+        }
+        if self.width_step > 0 {
+            if self.grow != Grow::Height {
+                bail!("--width-step only works with --grow height.");
+            }
+            if self.grow_width_after.is_some() {
+                bail!("--width-step cannot be used with --grow-width-after.");
+            }
+        }
+        if self.view_interval == Some(0) {
+            bail!("The view interval must be positive.");
+        }
+        if self.survey.is_some() && (self.threads > 1 || self.round_robin.is_some()) {
+            bail!("--survey cannot be used with --threads or --round-robin.");
+        }
+        if let DisplayPhase::Fixed(gen) = self.display_phase {
+            if gen >= target.period {
+                bail!(
+                    "The displayed generation {} must be less than the period {}.",
+                    gen,
+                    target.period
+                );
+            }
+        }
+        if self.threads > 1 && self.tui {
+            bail!("--tui cannot be used with --threads.");
+        }
+        if self.threads > 1 && self.round_robin.is_some() {
+            bail!("--threads cannot be used with --round-robin.");
+        }
+        if self.threads > 1 && self.grow != Grow::Height {
+            bail!("--threads can only be used with --grow height.");
+        }
+        if self.start_width() > self.max_width {
+            bail!("The initial width is larger than the maximum width.");
+        }
+        if self.search_order == Some(SearchOrder::Diagonal)
+            && self.start_width() != self.init_height
+        {
+            bail!(
+                "The diagonal search order requires a square world, but the \
+                 initial world is {}x{}. Set the width and the height to the \
+                 same value, or use another search order.",
+                self.start_width(),
+                self.init_height
+            );
+        }
+        if self.min_width.is_some_and(|w| w > self.max_width) {
+            bail!("The minimum width is larger than the maximum width.");
+        }
+        if !is_compatible(self.symmetry, target.dx, target.dy) {
+            let compatible = compatible_symmetries(target.dx, target.dy)
+                .iter()
+                .map(Symmetry::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            bail!(
+                "Symmetry {} is incompatible with the translation ({}, {}): \
+                 a spaceship with this symmetry cannot move in this direction. \
+                 Compatible symmetries: {}.",
+                self.symmetry,
+                target.dx,
+                target.dy,
+                compatible
+            );
+        }
+        Ok(())
+    }
+
+    /// Reads the known cells from the seed file.
+    fn read_seed(&self, seed: &Path, target: &Target) -> Result<Vec<KnownCell>> {
+        let mut buffer = String::new();
+        File::open(seed)?.read_to_string(&mut buffer)?;
+        let known_cells = KnownCell::from_rles(buffer.as_str())?;
+        for cell in &known_cells {
+            let (x, y, t) = cell.coord;
+            if x >= self.start_width() {
+                bail!(
+                    "The seed is wider than the initial width {}.",
+                    self.start_width()
+                );
+            }
+            if y >= self.init_height {
+                bail!(
+                    "The seed is taller than the initial height {}.",
+                    self.init_height
+                );
+            }
+            if t >= target.period {
+                bail!(
+                    "The seed has more generations than the period {}.",
+                    target.period
+                );
+            }
+        }
+        Ok(known_cells)
+    }
+
+    fn sss(&self, target: &Target) -> Result<Sss> {
+        let cell_count = self.init_cell_count;
+        // The fields without an option, e.g., the known cells, come from
+        // `--from-config`.
+        let base = self.base_config.clone().unwrap_or_default();
+        let config = Config {
+            width: self.start_width(),
+            height: self.init_height,
+            period: target.period,
+            ..base
+        }
+        .set_translate(target.dx, target.dy)
+        .set_symmetry(self.symmetry)
+        .set_search_order(self.search_order.clone())
+        .set_rule_string(self.rule.clone())
+        .set_new_state(self.new_state)
+        .set_max_cell_count(if cell_count > 0 {
+            Some(cell_count - 1)
+        } else {
+            None
+        })
+        .set_reduce_max(!self.no_reduce);
+        let config = match &self.seed {
+            Some(seed) => config.set_known_cells(self.read_seed(seed, target)?),
+            None => config,
+        };
+        let world = config.world()?;
+        let mut sss = Sss::new(world, cell_count);
+        sss.grow = self.grow;
+        sss.view_freq = self.init_view_freq();
+        Ok(sss)
+    }
+
+    /// Number of steps of the first view.
+    fn init_view_freq(&self) -> u64 {
+        match self.view_interval {
+            Some(_) => INIT_VIEW_FREQ,
+            None => self.view_freq,
+        }
+    }
+}
+
+/// Why the search stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Stop {
+    /// `--timeout` is reached.
+    Timeout,
+    /// `--max-results` or `--stop-after-first` is reached.
+    MaxResults,
+    /// No more result can be found at `--max-height`,
+    /// or at `--max-width` when the width grows.
+    MaxSize,
+    /// The turn of a round-robin search is over.
+    Turn,
+    /// `--max-memory` is exceeded.
+    MaxMemory,
+    /// `q` is pressed in `--tui`.
+    Quit,
+    /// The search is cancelled, e.g., `Ctrl-C` is pressed.
+    Interrupted,
+    /// `--max-time` or `--max-steps` is reached.
+    Budget,
+}
+
+impl Stop {
+    /// The reason of the stop, for the event log.
+    fn reason(self) -> &'static str {
+        match self {
+            Stop::Timeout => "timeout",
+            Stop::MaxResults => "enough results",
+            Stop::MaxSize => "maximum size",
+            Stop::Turn => "end of the turn",
+            Stop::MaxMemory => "memory limit",
+            Stop::Quit => "quit",
+            Stop::Interrupted => "interrupted",
+            Stop::Budget => "budget",
+        }
+    }
+
+    /// Whether the whole run stops, not only the search for this target.
+    fn aborts_run(self) -> bool {
+        matches!(
+            self,
+            Stop::MaxMemory | Stop::Interrupted | Stop::Quit | Stop::Budget
+        )
+    }
+}
+
+/// The exit code when `--max-memory` is exceeded.
+const EXIT_MAX_MEMORY: i32 = 3;
+
+/// The exit code when `--max-time` or `--max-steps` is reached.
+const EXIT_BUDGET: i32 = 10;
+
+/// The exit code when `Ctrl-C` is pressed.
+const EXIT_INTERRUPTED: i32 = 130;
+
+/// The key of the exhausted heights in the extra fields of the save file.
+const EXHAUSTED_KEY: &str = "exhausted_heights";
+
+/// The key of the growth mode in the extra fields of the save file.
+const GROW_KEY: &str = "grow";
+
+/// The key of the time spent on the search in the extra fields of the save
+/// file.
+const TIMES_KEY: &str = "times";
+
+/// The key of the number of search steps in the extra fields of the save
+/// file.
+const STEPS_KEY: &str = "steps";
+
+/// The key of the number of results found in the extra fields of the save
+/// file.
+const FOUND_KEY: &str = "found";
+
+/// The key of the cell count and the apgcode of the smallest result in the
+/// extra fields of the save file, separated by a space.
+const BEST_KEY: &str = "best";
+
+/// The name of the partial pattern written on `SIGUSR1`.
+const PARTIAL_NAME: &str = "partial.rle";
+
+/// Number of recent views used to compute the search speed.
+const SPEED_WINDOW: usize = 8;
+
+/// Maximum number of steps between two checks of `Ctrl-C` and `SIGUSR1`.
+#[cfg(not(test))]
+const CANCEL_STEPS: u64 = 1_000_000;
+
+/// Fewer steps in the tests, which run without optimization.
+#[cfg(test)]
+const CANCEL_STEPS: u64 = 1_000;
+
+/// Number of spaces between two phases with `--show-all-phases`.
+const PHASE_GUTTER: usize = 2;
+
+/// Number of steps of the first view with `--view-interval`.
+const INIT_VIEW_FREQ: u64 = 10_000;
+
+/// Minimum number of steps of a view with `--view-interval`.
+const MIN_VIEW_FREQ: u64 = 1_000;
+
+/// Spaceship Search
+struct Sss {
+    cell_count: u32,
+    gen: i32,
+    world: PolyWorld,
+    /// Time spent on this search, including the previous runs.
+    timer: Timer,
+    /// Elapsed times and total steps at the recent views, for computing
+    /// the search speed.
+    view_times: VecDeque<(Duration, u64)>,
+    /// Number of steps between two views.
+    view_freq: u64,
+    trend: Trend,
+    /// Cell count and apgcode of the smallest spaceship found, including
+    /// the previous runs.
+    best: Option<(u32, String)>,
+    /// The height when this run started.
+    init_height: i32,
+    /// The width when this run started.
+    init_width: i32,
+    /// Which dimension grows when no more result can be found.
+    grow: Grow,
+    /// Canonical forms of the results saved in this run.
+    canonical: HashSet<String>,
+    /// Heights where no more result can be found, recorded in the save file.
+    exhausted: BTreeSet<i32>,
+    /// Wall-clock time of this run at the last result or the last change
+    /// of the height.
+    height_start: Duration,
+    stats: SearchStats,
+    /// Number of search steps in the previous runs, loaded from the save
+    /// file.
+    saved_steps: u64,
+    /// Number of results found in the previous runs, loaded from the save
+    /// file.
+    saved_found: u32,
+    /// Number of increases of the height since the last result or the last
+    /// increase of the width, for `--grow-width-after`.
+    heights_without_result: u32,
+    trace: Option<Trace>,
+    event_log: Option<EventLog>,
+    stats_file: Option<StatsFile>,
+    /// The result directory whose manifest records the files of this search,
+    /// if any.
+    manifest_dir: Option<PathBuf>,
+    /// Elapsed time at the last result in this run, if any.
+    last_found: Option<Duration>,
+    /// Whether a status line of `--progress-line` is not yet ended.
+    progress_pending: bool,
+    tui: TuiView,
+    /// Whether no file is written, for `--dry-run`.
+    dry_run: bool,
+    /// The state shared with the other threads in a parallel search.
+    shared: Option<Arc<Shared>>,
+}
+
+impl Sss {
+    fn new(world: PolyWorld, cell_count: u32) -> Self {
+        let init_height = world.config().height;
+        let init_width = world.config().width;
+        Sss {
+            cell_count,
+            gen: 0,
+            world,
+            timer: Timer::default(),
+            view_times: VecDeque::with_capacity(SPEED_WINDOW + 1),
+            view_freq: 0,
+            trend: Trend::default(),
+            best: None,
+            init_height,
+            init_width,
+            grow: Grow::Height,
+            canonical: HashSet::new(),
+            exhausted: BTreeSet::new(),
+            height_start: Duration::default(),
+            stats: SearchStats::default(),
+            saved_steps: 0,
+            saved_found: 0,
+            heights_without_result: 0,
+            trace: None,
+            event_log: None,
+            stats_file: None,
+            manifest_dir: None,
+            last_found: None,
+            progress_pending: false,
+            tui: TuiView::default(),
+            dry_run: false,
+            shared: None,
+        }
+    }
+
+    fn from_save<P: AsRef<Path>>(save: P) -> Result<Self> {
+        let mut bytes = Vec::new();
+        File::open(&save)?.read_to_end(&mut bytes)?;
+        let buffer = compress::decompress(bytes)?;
+        let ser = from_str::<WorldSer>(&buffer)?;
+        let exhausted = match ser.extra.get(EXHAUSTED_KEY) {
+            Some(heights) => heights
+                .split(',')
+                .filter(|h| !h.is_empty())
+                .map(str::parse)
+                .collect::<Result<_, _>>()?,
+            None => BTreeSet::new(),
+        };
+        let grow = match ser.extra.get(GROW_KEY) {
+            Some(grow) => grow.parse().map_err(anyhow::Error::msg)?,
+            None => Grow::Height,
+        };
+        let times = match ser.extra.get(TIMES_KEY) {
+            Some(times) => times.parse()?,
+            None => Times::default(),
+        };
+        let steps = match ser.extra.get(STEPS_KEY) {
+            Some(steps) => steps.parse()?,
+            None => 0,
+        };
+        let found = match ser.extra.get(FOUND_KEY) {
+            Some(found) => found.parse()?,
+            None => 0,
+        };
+        let best = match ser.extra.get(BEST_KEY) {
+            Some(best) => match best.split_once(' ') {
+                Some((cell_count, apgcode)) => Some((cell_count.parse()?, apgcode.to_owned())),
+                None => bail!("Invalid smallest result {} in the save file.", best),
+            },
+            None => None,
+        };
+        let mut world = ser.world()?;
+        // The save may be older than the last increase of the height.
+        while grow == Grow::Height && exhausted.contains(&world.config().height) {
+            let mut config = world.config().clone();
+            config.height += 1;
+            world = config.world()?;
+        }
+        let mut sss = Sss::new(world, 0);
+        sss.cell_count = sss.bound();
+        sss.exhausted = exhausted;
+        sss.grow = grow;
+        sss.timer = Timer::new(times);
+        sss.saved_steps = steps;
+        sss.saved_found = found;
+        sss.best = best;
+        Ok(sss)
+    }
+
+    /// The upper bound of the cell count in the world plus 1,
+    /// or 0 if there is no bound.
+    fn bound(&self) -> u32 {
+        self.world
+            .config()
+            .max_cell_count
+            .map(|i| i + 1)
+            .unwrap_or(0)
+    }
+
+    /// Loads the search status from the save file,
+    /// or from its backups if the save file is corrupted.
+    fn from_saves<P: AsRef<Path>>(save: P, rotate: u32) -> Result<Self> {
+        let save = save.as_ref();
+        let result = Sss::from_save(save);
+        if result.is_ok() {
+            return result;
+        }
+        for i in 1..=rotate {
+            let backup = with_suffix(save, &format!(".{}", i));
+            if let Ok(sss) = Sss::from_save(&backup) {
+                println!("Loaded the backup {}.", backup.display());
+                return Ok(sss);
+            }
+        }
+        result
+    }
+
+    /// Records the end of a view.
+    fn record_view(&mut self) {
+        if self.view_times.len() > SPEED_WINDOW {
+            self.view_times.pop_front();
+        }
+        self.view_times
+            .push_back((self.timer.elapsed(), self.stats.steps));
+    }
+
+    /// Average number of steps per second over the recent views.
+    fn speed(&self) -> Option<f64> {
+        let (first_time, first_steps) = self.view_times.front()?;
+        let (last_time, last_steps) = self.view_times.back()?;
+        let secs = (*last_time - *first_time).as_secs_f64();
+        if secs > 0.0 {
+            Some((last_steps - first_steps) as f64 / secs)
+        } else {
+            None
+        }
+    }
+
+    /// Number of search steps, including the previous runs.
+    fn total_steps(&self) -> u64 {
+        self.saved_steps + self.stats.steps
+    }
+
+    /// Number of results found, including the previous runs.
+    fn total_found(&self) -> u32 {
+        self.saved_found + self.stats.found
+    }
+
+    /// Average number of steps per second, including the previous runs.
+    fn average_speed(&self) -> Option<f64> {
+        let secs = self.timer.times().active.as_secs_f64();
+        if secs > 0.0 {
+            Some(self.total_steps() as f64 / secs)
+        } else {
+            None
+        }
+    }
+
+    /// Scales the number of steps of the next view, so that it takes about
+    /// `interval`, given that the last full view took `elapsed`.
+    ///
+    /// The number is at most doubled or halved at a time, so that a single
+    /// unusual view does not throw it off.
+    fn adjust_view_freq(&mut self, elapsed: Duration, interval: Duration) {
+        let factor = if elapsed.is_zero() {
+            2.0
+        } else {
+            (interval.as_secs_f64() / elapsed.as_secs_f64()).clamp(0.5, 2.0)
+        };
+        self.view_freq = ((self.view_freq as f64 * factor) as u64).max(MIN_VIEW_FREQ);
+    }
+
+    /// The status line.
+    ///
+    /// `CELLS` is the upper bound of the cell count plus 1, and `LIVE` is the
+    /// number of living cells in the displayed generation `gen`. `TIME` and
+    /// `STEPS` include the previous runs, and `SPEED` is over the recent
+    /// views.
+    /// With `--verbose`, `AVG` is the speed over the whole search, and
+    /// `CONFLICTS` is the number of conflicts at this size, which is shown
+    /// as `BACKTRACKS` with `--track-backtracks`.
+    fn info(&self, out: &Output, opt: &Opt, gen: i32) -> String {
+        let config = self.world.config();
+        let mut info = String::new();
+        if opt.several_targets() || opt.all_symmetries || opt.symmetries.is_some() {
+            let target = if self.target().is_oscillator() {
+                format!("OSC p{}", config.period)
+            } else {
+                self.target().to_string()
+            };
+            info += &format!("=TARGET:{}==SYM:{}=", target, config.symmetry);
+        }
+        if let Some(thread) = opt.thread {
+            info += &format!("=THREAD:{}=", thread);
+        }
+        info += &format!(
+            "=GEN:{}==HEIGHT:{}==CELLS:{}==LIVE:{}==TIME:{}",
+            gen,
+            self.world.config().height,
+            self.cell_count,
+            self.world.cell_count_gen(gen),
+            time::duration_compact(self.timer.total_elapsed())
+        );
+        info += &format!("==STEPS:{}", format_speed(self.total_steps() as f64));
+        if let Some(speed) = self.speed() {
+            info += &format!("==SPEED:{}/s", format_speed(speed));
+        }
+        if opt.verbose {
+            if let Some(speed) = self.average_speed() {
+                info += &format!("==AVG:{}/s", format_speed(speed));
+            }
+            if !opt.track_backtracks {
+                info += &format!("==CONFLICTS:{}", self.world.conflicts());
+            }
+        }
+        if opt.track_backtracks {
+            info += &format!("==BACKTRACKS:{}", self.world.conflicts());
+        }
+        if self.stats.subperiod > 0 {
+            info += &format!("==SUBPERIOD:{}", self.stats.subperiod);
+        }
+        if self.stats.narrow > 0 {
+            info += &format!("==NARROW:{}", self.stats.narrow);
+        }
+        if self.stats.small > 0 {
+            info += &format!("==SMALL:{}", self.stats.small);
+        }
+        if opt.verbose && self.trend.bound().is_some() {
+            info += &format!(
+                "==PEAK:{}==NEAR:{}",
+                self.trend.peak, self.trend.near_misses
+            );
+        }
+        format!("{:=<1$}", info, out.line_width())
+    }
+
+    /// Prints the status line, overwriting the previous one.
+    fn progress_line(&mut self, out: &Output, opt: &Opt) {
+        let gen = self.displayed_gen(opt);
+        print!("\r{}", out.paint_info(&self.info(out, opt, gen)));
+        stdout().flush().ok();
+        self.progress_pending = true;
+    }
+
+    /// Ends the status line printed by `--progress-line`, if any,
+    /// before printing anything else.
+    fn end_progress_line(&mut self) {
+        if self.progress_pending {
+            println!();
+            self.progress_pending = false;
+        }
+    }
+
+    /// Prints the status line and the world.
+    ///
+    /// The world is truncated to the width of the terminal,
+    /// but not when the output is not a terminal.
+    fn display(&self, out: &Output, opt: &Opt, view: View, gen: i32) {
+        println!("{}", out.paint_info(&self.info(out, opt, gen)));
+        let width = match out.max_world_width() {
+            Some(max_width) => self.world.config().width.min(max_width),
+            None => self.world.config().width,
+        };
+        let mut display = String::new();
+        match self.all_phases(out).filter(|_| opt.show_all_phases) {
+            Some(phases) => {
+                for row in phases {
+                    display += &row;
+                    display.push('\n');
+                }
+            }
+            None => {
+                for row in self.world_rows(gen, width, out.braille) {
+                    display += &row;
+                    display.push('\n');
+                }
+            }
+        }
+        print!("{}", out.paint_world(view, &display));
+    }
+
+    /// The rows of all phases side by side, after a row of the cell counts
+    /// of the phases, or `None` if they do not fit in the terminal.
+    fn all_phases(&self, out: &Output) -> Option<Vec<String>> {
+        let config = self.world.config();
+        let width = if out.braille {
+            (config.width as usize).div_ceil(2)
+        } else {
+            config.width as usize
+        };
+        let period = config.period as usize;
+        let total = period * width + (period - 1) * PHASE_GUTTER;
+        if out.width.is_some_and(|term_width| total > term_width - 1) {
+            return None;
+        }
+        let gutter = " ".repeat(PHASE_GUTTER);
+        let labels: Vec<_> = (0..config.period)
+            .map(|gen| {
+                let label = format!("{}:{}", gen, self.world.cell_count_gen(gen));
+                format!("{:<1$.1$}", label, width)
+            })
+            .collect();
+        let phases: Vec<_> = (0..config.period)
+            .map(|gen| self.world_rows(gen, config.width, out.braille))
+            .collect();
+        let mut rows = vec![labels.join(&gutter).trim_end().to_owned()];
+        for y in 0..phases[0].len() {
+            let row: Vec<_> = phases.iter().map(|phase| phase[y].as_str()).collect();
+            rows.push(row.join(&gutter));
+        }
+        Some(rows)
+    }
+
+    /// The rows of the world at the generation, up to the width.
+    fn world_rows(&self, gen: i32, width: i32, braille: bool) -> Vec<String> {
+        if braille {
+            return self.braille_rows(gen, width);
+        }
+        (0..self.world.config().height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        display_char(
+                            self.world.get_cell_state((x, y, gen)),
+                            self.world.is_gen_rule(),
+                        )
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The rows of the world at the generation, up to the width, in
+    /// Braille characters of 2x4 cells.
+    fn braille_rows(&self, gen: i32, width: i32) -> Vec<String> {
+        // The bits of the dots, by the row and the column in a character.
+        const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+        let height = self.world.config().height;
+        (0..height)
+            .step_by(4)
+            .map(|y0| {
+                (0..width)
+                    .step_by(2)
+                    .map(|x0| {
+                        let mut bits = 0;
+                        for (dy, row) in DOTS.iter().enumerate() {
+                            for (dx, bit) in row.iter().enumerate() {
+                                let (x, y) = (x0 + dx as i32, y0 + dy as i32);
+                                if x < width && y < height {
+                                    if let Some(state) = self.world.get_cell_state((x, y, gen)) {
+                                        if state != DEAD {
+                                            bits |= bit;
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        char::from_u32(0x2800 + bits).unwrap_or(' ')
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The generation to display during the search, by `--display-phase`.
+    fn displayed_gen(&self, opt: &Opt) -> i32 {
+        let period = self.world.config().period;
+        match opt.display_phase {
+            DisplayPhase::Cycle => self.gen,
+            DisplayPhase::Min => (0..period)
+                .min_by_key(|&gen| self.world.cell_count_gen(gen))
+                .unwrap_or(0),
+            DisplayPhase::Fixed(gen) => gen % period,
+        }
+    }
+
+    /// Redraws the full-screen display of `--tui`.
+    fn draw_tui(&self, out: &Output, opt: &Opt) {
+        let (term_width, term_height) = dimensions().unwrap_or((80, 24));
+        let config = self.world.config();
+        let gen = (self.displayed_gen(opt) + self.tui.gen_offset).rem_euclid(config.period);
+        let max_width = term_width as i32 - 1;
+        let max_width = if out.braille {
+            max_width * 2
+        } else {
+            max_width
+        };
+        let width = config.width.min(max_width);
+        // The status line, and three lines of the footer.
+        let rows = (term_height as i32 - 5).max(1);
+        let world = self.world_rows(gen, width, out.braille);
+        let height = world.len() as i32;
+        let scroll = self.tui.scroll.clamp(0, (height - rows).max(0));
+        let mut lines = vec![out.paint_info(&self.info(out, opt, gen))];
+        lines.extend(
+            world
+                .iter()
+                .skip(scroll as usize)
+                .take(rows as usize)
+                .map(|row| out.paint_world(View::Searching, row)),
+        );
+        lines.push(format!(
+            "Generation {} of {}, rows {} to {} of {}{}",
+            gen,
+            config.period,
+            scroll,
+            (scroll + rows).min(height) - 1,
+            height,
+            if self.tui.paused { " - PAUSED" } else { "" }
+        ));
+        lines.push(self.tui.message.clone().unwrap_or_default());
+        lines.push(String::from(tui::KEYS));
+        tui::draw(&lines);
+    }
+
+    /// Handles the keys of `--tui`, and waits while the search is paused.
+    ///
+    /// Returns `Stop::Quit` after saving when `q` is pressed.
+    fn handle_keys<P: AsRef<Path>>(
+        &mut self,
+        out: &Output,
+        opt: &Opt,
+        save: P,
+        cancel: &CancelToken,
+    ) -> Result<Option<Stop>> {
+        loop {
+            let timeout = if self.tui.paused { 100 } else { 0 };
+            match tui::read_key(timeout) {
+                Some(b'q') => {
+                    self.write_save(&save, opt.save_rotate)?;
+                    return Ok(Some(Stop::Quit));
+                }
+                Some(b'p') => self.tui.paused = !self.tui.paused,
+                Some(b'+') | Some(b'=') => self.tui.gen_offset += 1,
+                Some(b'-') => self.tui.gen_offset -= 1,
+                Some(b's') => {
+                    self.write_save(&save, opt.save_rotate)?;
+                    self.tui.message = Some(format!("Saved at {}.", time::clock(opt.utc)));
+                }
+                Some(b'j') => {
+                    self.tui.scroll = (self.tui.scroll + 1).min(self.world.config().height - 1)
+                }
+                Some(b'k') => self.tui.scroll = (self.tui.scroll - 1).max(0),
+                Some(_) => continue,
+                None if self.tui.paused && !cancel.is_cancelled() => continue,
+                None => return Ok(None),
+            }
+            self.draw_tui(out, opt);
+        }
+    }
+
+    /// The period and the translation of the search.
+    fn target(&self) -> Target {
+        let config = self.world.config();
+        Target {
+            period: config.period,
+            dx: config.dx,
+            dy: config.dy,
+        }
+    }
+
+    /// The current generation, trimmed to its bounding box.
+    fn pattern(&self) -> Pattern {
+        Pattern::from_world(&self.world, self.gen)
+    }
+
+    /// The smallest period of the current result,
+    /// which is a divisor of the period of the search.
+    fn true_period(&self) -> i32 {
+        let config = self.world.config();
+        let period = config.period;
+        let first = Pattern::from_world(&self.world, 0);
+        (1..period)
+            .filter(|d| {
+                period % d == 0 && config.dx * d % period == 0 && config.dy * d % period == 0
+            })
+            .find(|&d| {
+                let phase = Pattern::from_world(&self.world, d);
+                let (x, y) = first.offset;
+                phase.rows == first.rows
+                    && phase.offset == (x - config.dx * d / period, y - config.dy * d / period)
+            })
+            .unwrap_or(period)
+    }
+
+    /// Width of the widest phase of the current result.
+    fn pattern_width(&self) -> i32 {
+        (0..self.world.config().period)
+            .map(|t| Pattern::from_world(&self.world, t).width() as i32)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The canonical apgcode of the current result.
+    ///
+    /// Returns `None` for rules with more than two states.
+    fn apgcode(&self) -> Option<String> {
+        if self.world.is_gen_rule() {
+            return None;
+        }
+        let phases: Vec<_> = (0..self.world.config().period)
+            .map(|t| Pattern::from_world(&self.world, t).grid())
+            .collect();
+        Some(apgcode(
+            &phases,
+            self.world.config().period,
+            !self.target().is_oscillator(),
+        ))
+    }
+
+    /// Whether the current result comes back to the same place after a
+    /// period, i.e., it is an oscillator, although the search has a
+    /// translation. Only rules with two states are checked.
+    fn is_stationary(&self) -> bool {
+        if self.target().is_oscillator() || self.world.is_gen_rule() {
+            return false;
+        }
+        let grid = self.pattern().grid();
+        let living: Vec<_> = grid
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, &alive)| alive)
+                    .map(move |(x, _)| (x as i32, y as i32))
+            })
+            .collect();
+        let config = self.world.config();
+        matches!(
+            phases(&config.rule_string, &living, config.period),
+            Ok(Some((_, (0, 0))))
+        )
+    }
+
+    /// A canonical form of the current result, which is the same for all
+    /// phases and orientations of a spaceship.
+    ///
+    /// This is the apgcode, or the RLE of the current generation for rules
+    /// with more than two states, in the orientation of `--canonicalize`.
+    fn canonical(&self, opt: &Opt) -> String {
+        self.apgcode().unwrap_or_else(|| {
+            let pattern = self.pattern();
+            if opt.canonicalize {
+                pattern.transform(self.canonical_transform()).rle_line()
+            } else {
+                pattern.rle_line()
+            }
+        })
+    }
+
+    /// Metadata of the current result, written as comments in the result file.
+    fn comments(&self, opt: &Opt, pattern: &Pattern) -> Vec<String> {
+        let config = self.world.config();
+        let (dx, dy, symmetry) = self.output_target(opt);
+        let target = Target {
+            period: config.period,
+            dx,
+            dy,
+        };
+        let found = format!("{} {}", target, target.noun());
+        let mut comments = vec![
+            format!("{} found by spaceships.", found),
+            format!("Symmetry: {}", symmetry),
+            format!(
+                "Apgcode: {}",
+                self.apgcode()
+                    .as_deref()
+                    .unwrap_or("unsupported for this rule")
+            ),
+            format!("Rule: {}", config.rule_string),
+            format!(
+                "Search world: width {}, height {}",
+                config.width, config.height
+            ),
+            format!(
+                "Search order: {}, new state: {}",
+                search_order_name(&config.search_order),
+                new_state_name(config.new_state)
+            ),
+        ];
+        if opt.align_top {
+            let (x, y) = pattern.offset;
+            comments.push(format!("Offset: x = {}, y = {}", x, y));
+        }
+        comments.push(format!("Search time: {}", time::times(self.timer.times())));
+        comments.push(format!("Date: {}", time::timestamp()));
+        comments
+    }
+
+    /// The filename of the current result, without the extension.
+    fn filename_stem(&self, opt: &Opt) -> Result<String> {
+        let values = self.placeholders(opt, self.stats.saved + 1);
+        // The default template has no translation for oscillators.
+        let template = if self.target().is_oscillator() && opt.name_template == DEFAULT_TEMPLATE {
+            OSCILLATOR_TEMPLATE
+        } else {
+            &opt.name_template
+        };
+        let name = render(template, &values)?;
+        let extension = format!(".{}", opt.format.extension());
+        Ok(sanitize_filename(name.trim_end_matches(&extension)))
+    }
+
+    /// The values of the placeholders of the filename template, with the
+    /// number `seq` of the result in this run.
+    fn placeholders(&self, opt: &Opt, seq: u32) -> [(&'static str, String); PLACEHOLDERS.len()] {
+        let config = self.world.config();
+        let (dx, dy, symmetry) = self.output_target(opt);
+        [
+            ("cells", self.cell_count.to_string()),
+            ("period", config.period.to_string()),
+            ("dx", dx.to_string()),
+            ("dy", dy.to_string()),
+            ("height", config.height.to_string()),
+            ("width", config.width.to_string()),
+            ("rule", sanitize_rule(&config.rule_string)),
+            ("symmetry", format!("{:?}", symmetry)),
+            ("timestamp", time::compact_timestamp()),
+            ("seq", seq.to_string()),
+        ]
+    }
+
+    /// The transformation of `--canonicalize`: the one that gives the
+    /// smallest row-major bit string of the current generation, with ties
+    /// broken by the width and then the RLE.
+    fn canonical_transform(&self) -> Transform {
+        let pattern = self.pattern();
+        Transform::ALL
+            .iter()
+            .copied()
+            .min_by_key(|&transform| {
+                let transformed = pattern.transform(transform);
+                let bits: Vec<bool> = transformed.grid().concat();
+                (bits, transformed.width(), transformed.rle())
+            })
+            .unwrap_or(Transform::Id)
+    }
+
+    /// The transformation from the search world to the result files.
+    ///
+    /// With `--canonical-output`, of the canonical orientations of the
+    /// current generation, the one with the smallest RLE is chosen, so that
+    /// a spaceship and its mirror image found by another search are written
+    /// the same way. With `--canonicalize` or `--align-top`, their
+    /// orientations are used instead.
+    fn output_transform(&self, opt: &Opt) -> Transform {
+        let config = self.world.config();
+        if opt.canonicalize {
+            return self.canonical_transform();
+        }
+        if opt.align_top {
+            return self.pattern().top_transform(config.dx, config.dy);
+        }
+        if !opt.canonical_output {
+            return Transform::Id;
+        }
+        let pattern = self.pattern();
+        canonical_transforms(config.dx, config.dy)
+            .into_iter()
+            .min_by_key(|&transform| pattern.transform(transform).rle())
+            .unwrap_or(Transform::Id)
+    }
+
+    /// The translation and the symmetry of the result files.
+    fn output_target(&self, opt: &Opt) -> (i32, i32, Symmetry) {
+        let config = self.world.config();
+        let transform = self.output_transform(opt);
+        let (dx, dy) = act_on_vector(transform, (config.dx, config.dy));
+        (dx, dy, transform_symmetry(config.symmetry, transform))
+    }
+
+    /// The generation `t` in the orientation of the result files.
+    fn output_pattern(&self, opt: &Opt, t: i32) -> Pattern {
+        Pattern::from_world(&self.world, t).transform(self.output_transform(opt))
+    }
+
+    /// Saves the current generation as a result.
+    ///
+    /// Returns the filename, or `None` if the same result is already saved.
+    fn write_result(&self, opt: &Opt) -> Result<Option<PathBuf>> {
+        let pattern = self.output_pattern(opt, self.gen);
+        let encoded = pattern.encode(opt.format);
+        if self.dry_run {
+            println!("{}", encoded.trim_end());
+            return Ok(None);
+        }
+        let stem = self.filename_stem(opt)?;
+        let filename = match unique_path(&opt.dir, &stem, opt.format.extension(), &encoded)? {
+            Some(filename) => filename,
+            None => return Ok(None),
+        };
+        let mut file = File::create(&filename)?;
+        if !opt.no_comments {
+            if let Some(prefix) = opt.format.comment_prefix() {
+                for comment in self.comments(opt, &pattern) {
+                    writeln!(file, "{}{}", prefix, comment)?;
+                }
+            }
+        }
+        write!(file, "{}", encoded)?;
+        self.register(&filename, Category::PrimaryResult)?;
+        let config = self.world.config();
+        let (dx, dy, symmetry) = self.output_target(opt);
+        let gallery = gallery::add(
+            &opt.dir,
+            gallery::Entry {
+                file: filename
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned(),
+                cell_count: self.cell_count,
+                period: config.period,
+                dx,
+                dy,
+                symmetry: symmetry.to_string(),
+                rule: config.rule_string.clone(),
+                apgcode: self.apgcode(),
+                pattern: encoded.clone(),
+            },
+        )?;
+        self.register(&gallery, Category::Derived)?;
+        if opt.svg {
+            let style = SvgStyle {
+                cell_size: opt.svg_cell_size,
+                color: opt.svg_color.clone(),
+                background: opt.svg_background.clone(),
+            };
+            let path = filename.with_extension("svg");
+            std::fs::write(&path, svg::svg(&pattern, &style)?)?;
+            if let Some(dir) = &self.manifest_dir {
+                manifest::register(dir, &path, Category::Derived, true)?;
+            }
+        }
+        if opt.all_phases {
+            self.write_phases(opt, &filename)?;
+        }
+        if opt.emit_config {
+            let path = filename.with_extension("toml");
+            // The bound is already reduced below this result.
+            let config = self
+                .world
+                .config()
+                .clone()
+                .set_max_cell_count(Some(self.cell_count));
+            let sidecar = Sidecar {
+                max_width: opt.max_width,
+                config,
+            };
+            sidecar.write(&path)?;
+            self.register(&path, Category::Derived)?;
+        }
+        Ok(Some(filename))
+    }
+
+    /// Writes every phase of the current result to a subdirectory named
+    /// after the result file.
+    fn write_phases(&self, opt: &Opt, filename: &Path) -> Result<()> {
+        let name = filename.file_name().unwrap_or_default().to_string_lossy();
+        let stem = filename.file_stem().unwrap_or_default().to_string_lossy();
+        let dir = filename.with_file_name(format!("{}_phases", stem));
+        create_dir_all(&dir)?;
+        for t in 0..self.world.config().period {
+            let path = dir.join(format!("phase_{}.{}", t, opt.format.extension()));
+            let mut file = File::create(&path)?;
+            if !opt.no_comments {
+                if let Some(prefix) = opt.format.comment_prefix() {
+                    writeln!(file, "{}Phase {} of {}", prefix, t, name)?;
+                }
+            }
+            let gen = (self.gen + t) % self.world.config().period;
+            write!(file, "{}", self.output_pattern(opt, gen).encode(opt.format))?;
+            if let Some(manifest_dir) = &self.manifest_dir {
+                manifest::register(manifest_dir, &path, Category::Derived, true)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends the current result to the results log.
+    fn append_result_log(&self, opt: &Opt, filename: &Path) -> Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        let config = self.world.config();
+        let (dx, dy, symmetry) = self.output_target(opt);
+        let times = self.timer.times();
+        let entry: [(&str, Value); 20] = [
+            ("schema_version", json!(results::SCHEMA_VERSION)),
+            ("timestamp", json!(time::timestamp())),
+            ("wall_secs", json!(times.wall.as_secs_f64())),
+            ("active_secs", json!(times.active.as_secs_f64())),
+            ("cpu_secs", json!(times.cpu.as_secs_f64())),
+            ("suspended_secs", json!(times.suspended.as_secs_f64())),
+            ("cell_count", json!(self.cell_count)),
+            ("period", json!(config.period)),
+            ("dx", json!(dx)),
+            ("dy", json!(dy)),
+            ("symmetry", json!(symmetry.to_string())),
+            ("rule", json!(config.rule_string)),
+            ("height", json!(config.height)),
+            ("width", json!(config.width)),
+            ("gen", json!(self.gen)),
+            (
+                "search_order",
+                json!(search_order_name(&config.search_order)),
+            ),
+            ("new_state", json!(new_state_name(config.new_state))),
+            ("rle", json!(self.output_pattern(opt, self.gen).rle_line())),
+            ("apgcode", json!(self.apgcode())),
+            (
+                "file",
+                json!(filename
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())),
+            ),
+        ];
+        results::append(opt.log_path(), opt.log_format, &entry)?;
+        self.register(&opt.log_path(), Category::Log)
+    }
+
+    /// Undoes the reduction of the bound by rlifesrc after a result that
+    /// is skipped.
+    fn restore_bound(&mut self) {
+        let bound = self.cell_count.checked_sub(1);
+        self.world.set_max_cell_count(bound);
+    }
+
+    /// Applies `--override-rule` and `--override-max-width` to a resumed
+    /// search, starting the search at the current size again.
+    fn apply_overrides(&mut self, opt: &Opt) -> Result<()> {
+        if opt.override_rule.is_none() && opt.override_max_width.is_none() {
+            return Ok(());
+        }
+        let mut config = self.world.config().clone();
+        if let Some(rule) = &opt.override_rule {
+            config.rule_string = rule.clone();
+        }
+        if let Some(width) = opt.override_max_width {
+            config.width = match self.grow {
+                Grow::Height if opt.width_step == 0 => width,
+                _ => config.width.min(width),
+            };
+        }
+        self.world = config.world()?;
+        self.gen = 0;
+        self.view_times.clear();
+        self.trend.reset();
+        if opt.thread.unwrap_or(0) == 0 {
+            println!(
+                "Overriding the save file with the rule {} and the width {}; \
+                 searching height {} again.",
+                config.rule_string, config.width, config.height
+            );
+        }
+        Ok(())
+    }
+
+    /// Lowers the upper bound of the cell count to the one shared by the
+    /// other threads, if it is lower.
+    fn sync_bound(&mut self) {
+        if let Some(bound) = self.shared.as_ref().map(|shared| shared.bound()) {
+            self.lower_bound(bound);
+        }
+    }
+
+    /// Lowers the upper bound of the cell count to `bound - 1`,
+    /// unless `bound` is 0 or the bound is already lower.
+    fn lower_bound(&mut self, bound: u32) {
+        if bound > 0 && (self.bound() == 0 || bound < self.bound()) {
+            self.world.set_max_cell_count(Some(bound - 1));
+            self.cell_count = bound;
+        }
+    }
+
+    /// Shows the progress of the search in the process title.
+    fn set_title(&self, opt: &Opt) {
+        if opt.no_proctitle {
+            return;
+        }
+        let config = self.world.config();
+        let bound = match config.max_cell_count {
+            Some(max) => format!(" <={}", max),
+            None => String::new(),
+        };
+        let velocity = if self.target().is_oscillator() {
+            String::from("OSC")
+        } else {
+            format!("({},{})", config.dx, config.dy)
+        };
+        let title = format!(
+            "spaceships {} p{} {} h{}{} found:{}",
+            config.rule_string,
+            config.period,
+            velocity,
+            config.height,
+            bound,
+            self.total_found()
+        );
+        let short = format!("h{}{} f{}", config.height, bound, self.total_found());
+        proctitle::set(&title, &short);
+    }
+
+    /// Writes an event to the trace file, if any.
+    fn trace(&mut self, event: Event, cells: Option<u32>) -> Result<()> {
+        let snapshot = Snapshot {
+            wall: self.timer.run_times().wall,
+            steps: self.stats.steps,
+            height: self.world.config().height,
+            width: self.world.config().width,
+            bound: self.world.config().max_cell_count,
+            cells,
+        };
+        match &mut self.trace {
+            Some(trace) => trace.write(event, &snapshot),
+            None => Ok(()),
+        }
+    }
+
+    /// Records a file created by this search in the manifest, if any.
+    fn register(&self, path: &Path, category: Category) -> Result<()> {
+        match &self.manifest_dir {
+            Some(dir) => manifest::register(dir, path, category, false),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes a line to the event log, if any.
+    fn log(&self, event: &str, description: &str) -> Result<()> {
+        match &self.event_log {
+            Some(event_log) => event_log.write(event, description),
+            None => Ok(()),
+        }
+    }
+
+    /// Saves the search status, keeping `rotate` backups of the old ones.
+    ///
+    /// The status is first written to a temporary file, which then replaces
+    /// the save file, so that the save file is never partially written.
+    fn write_save<P: AsRef<Path>>(&self, save: P, rotate: u32) -> Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        let save = save.as_ref();
+        let tmp = with_suffix(save, ".tmp");
+        let file = File::create(&tmp)?;
+        let mut ser = self.world.ser();
+        if !self.exhausted.is_empty() {
+            let heights: Vec<_> = self.exhausted.iter().map(i32::to_string).collect();
+            ser.extra
+                .insert(EXHAUSTED_KEY.to_owned(), heights.join(","));
+        }
+        if self.grow != Grow::Height {
+            ser.extra.insert(GROW_KEY.to_owned(), self.grow.to_string());
+        }
+        ser.extra
+            .insert(TIMES_KEY.to_owned(), self.timer.times().to_string());
+        ser.extra
+            .insert(STEPS_KEY.to_owned(), self.total_steps().to_string());
+        if self.total_found() > 0 {
+            ser.extra
+                .insert(FOUND_KEY.to_owned(), self.total_found().to_string());
+        }
+        if let Some((cell_count, apgcode)) = &self.best {
+            ser.extra
+                .insert(BEST_KEY.to_owned(), format!("{} {}", cell_count, apgcode));
+        }
+        let file = compress::write_json(Compress::from_path(save), file, &ser)?;
+        file.sync_all()?;
+        if rotate > 0 && save.exists() {
+            for i in (1..rotate).rev() {
+                let backup = with_suffix(save, &format!(".{}", i));
+                if backup.exists() {
+                    let next = with_suffix(save, &format!(".{}", i + 1));
+                    rename(&backup, &next)?;
+                    self.register(&next, Category::Checkpoint)?;
+                }
+            }
+            let backup = with_suffix(save, ".1");
+            rename(save, &backup)?;
+            self.register(&backup, Category::Checkpoint)?;
+        }
+        rename(tmp, save)?;
+        self.register(save, Category::Checkpoint)?;
+        // Makes the renames durable, which is only possible on Unix.
+        #[cfg(unix)]
+        if let Some(dir) = save.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            File::open(dir)?.sync_all()?;
+        }
+        self.log("save", &save.display().to_string())?;
+        self.write_metrics(save)
+    }
+
+    /// Writes a small summary of the progress next to the save file,
+    /// for monitoring the search from another process.
+    fn write_metrics(&self, save: &Path) -> Result<()> {
+        let path = metrics_path(save);
+        let config = self.world.config();
+        let metrics = json!({
+            "elapsed_secs": self.timer.elapsed().as_secs_f64(),
+            "total_steps": self.total_steps(),
+            "height": config.height,
+            "width": config.width,
+            "cell_count_bound": config.max_cell_count,
+            "total_found": self.total_found(),
+            "last_found_at_secs": self.last_found.map(|t| t.as_secs_f64()),
+            "gen": self.gen,
+        });
+        let tmp = with_suffix(&path, ".tmp");
+        std::fs::write(&tmp, metrics.to_string() + "\n")?;
+        rename(&tmp, &path)?;
+        self.register(&path, Category::Log)
+    }
+
+    /// Prints a summary of this run when the search stops, writes it to
+    /// the event log, and to the results log if the log is in JSON lines.
+    fn print_summary(&self, opt: &Opt, stop: Stop) -> Result<()> {
+        let height = self.world.config().height;
+        let width = self.world.config().width;
+        let noun = self.target().noun();
+        let best = match &self.best {
+            Some((cell_count, apgcode)) => format!(
+                "Found {}, the smallest with {} cells ({}).",
+                self.target().count(self.total_found()),
+                cell_count,
+                apgcode
+            ),
+            None => format!("No {} found.", noun),
+        };
+        let exhausted = match self.grow {
+            Grow::Height => format!("heights {} to {}", self.init_height, height),
+            Grow::Width => format!(
+                "widths {} to {} at height {}",
+                self.init_width, width, height
+            ),
+            Grow::Both => format!(
+                "sizes from {}x{} to {}x{}",
+                self.init_width, self.init_height, width, height
+            ),
+        };
+        let stopped = if stop == Stop::MaxSize {
+            format!("Exhausted {}", exhausted)
+        } else {
+            format!(
+                "Stopped ({}) at height {}, width {},",
+                stop.reason(),
+                height,
+                width
+            )
+        };
+        let times = self.timer.times();
+        let stopped = format!(
+            "{} after {} steps in {}.",
+            stopped,
+            self.total_steps(),
+            time::times(times)
+        );
+        println!("{}\n{}", best, stopped);
+        self.log("summary", &format!("{} {}", best, stopped))?;
+        if opt.log_format == LogFormat::Jsonl && !self.dry_run {
+            let summary = json!({
+                "stop": stop.reason(),
+                "found": self.total_found(),
+                "best_cell_count": self.best.as_ref().map(|b| b.0),
+                "best_apgcode": self.best.as_ref().map(|b| &b.1),
+                "times": times.to_json(),
+                "total_steps": self.total_steps(),
+                "final_height": height,
+                "final_width": width,
+                "heights_exhausted": [self.init_height, height],
+                "widths_exhausted": [self.init_width, width],
+            });
+            results::append(opt.log_path(), opt.log_format, &[("summary", summary)])?;
+        }
+        Ok(())
+    }
+
+    /// Increases the height or the width by 1, depending on `--grow`,
+    /// and saves the search status. In a parallel search, it moves on to
+    /// the smallest height that no thread has searched yet instead.
+    ///
+    /// Returns `Stop::MaxSize` instead if the size is already the maximum.
+    fn next_size<P: AsRef<Path>>(&mut self, opt: &Opt, save: P) -> Result<Option<Stop>> {
+        let mut config = self.world.config().clone();
+        if opt.track_backtracks {
+            let description = format!(
+                "{} at height {}, width {}",
+                self.world.conflicts(),
+                config.height,
+                config.width
+            );
+            self.end_progress_line();
+            println!("Backtracks: {}.", description);
+            self.log("backtracks", &description)?;
+        }
+        if config.require_square_world() {
+            println!("The world must be square, so its size cannot grow.");
+            self.write_save(&save, opt.save_rotate)?;
+            return Ok(Some(Stop::MaxSize));
+        }
+        let steps_width = opt.width_step > 0 && config.width < opt.max_width;
+        let grows_width = steps_width
+            || self
+                .grow
+                .grows_width(config.width, config.height, opt.max_width, opt.max_height);
+        if grows_width {
+            if config.width >= opt.max_width {
+                self.write_save(&save, opt.save_rotate)?;
+                return Ok(Some(Stop::MaxSize));
+            }
+            config.width = (config.width + opt.width_step.max(1)).min(opt.max_width);
+        } else {
+            let height = match &self.shared {
+                Some(shared) => shared.claim_height(),
+                None => config.height + 1,
+            };
+            if opt.max_height.is_some_and(|h| height > h) {
+                self.write_save(&save, opt.save_rotate)?;
+                return Ok(Some(Stop::MaxSize));
+            }
+            config.height = height;
+            if opt.width_step > 0 {
+                config.width = opt.start_width();
+            }
+        }
+        let mut widens = grows_width;
+        if let Some(n) = opt.grow_width_after.filter(|_| !grows_width) {
+            self.heights_without_result += 1;
+            if self.heights_without_result >= n {
+                self.heights_without_result = 0;
+                config.width += 1;
+                widens = true;
+            }
+        }
+        self.world = config.world()?;
+        self.gen = 0;
+        self.view_times.clear();
+        self.trend.reset();
+        self.height_start = self.timer.run_times().wall;
+        self.set_title(opt);
+        if !grows_width {
+            self.stats.height_increases += 1;
+            self.trace(Event::Height, None)?;
+            let description = if opt.width_step > 0 {
+                format!("increased to {}, width {}", config.height, config.width)
+            } else {
+                format!("increased to {}", config.height)
+            };
+            self.log("height", &description)?;
+        }
+        if widens {
+            self.stats.width_increases += 1;
+            self.trace(Event::Width, None)?;
+            self.log("width", &format!("increased to {}", config.width))?;
+        }
+        self.write_save(&save, opt.save_rotate)?;
+        Ok(None)
+    }
+
+    /// Searches for `chunk` steps, and returns the status and the number of
+    /// steps.
+    ///
+    /// The lib does not report its steps, so the number is always `chunk`,
+    /// which is only an upper bound when a result is found or the search
+    /// space is exhausted before the end of the chunk. The step counts are
+    /// thus approximate, by less than a chunk for each such view.
+    fn search_chunk(&mut self, chunk: u64) -> Status {
+        // The lib stops after more than the given number of steps.
+        self.world.search(Some(chunk - 1))
+    }
+
+    /// Searches for a view of `view_freq` steps, but stops early when
+    /// `cancel` is cancelled. The partial pattern is dumped in the middle of
+    /// the view on `SIGUSR1`.
+    ///
+    /// Returns the status and the number of steps.
+    fn search_view(
+        &mut self,
+        out: &Output,
+        opt: &Opt,
+        view_freq: u64,
+        cancel: &CancelToken,
+    ) -> Result<(Status, u64)> {
+        let mut steps = 0;
+        loop {
+            let chunk = CANCEL_STEPS.min(view_freq - steps);
+            self.timer.start();
+            let status = self.search_chunk(chunk);
+            self.timer.stop();
+            steps += chunk;
+            if status != Status::Searching
+                || steps >= view_freq
+                || cancel.is_cancelled()
+                || (out.tui && tui::key_pending())
+            {
+                return Ok((status, steps));
+            }
+            if signal::take_dump() {
+                self.dump_partial(out, opt)?;
+            }
+        }
+    }
+
+    /// Shows the current partial pattern and writes it to `partial.rle`
+    /// in the result directory, without changing the search.
+    fn dump_partial(&mut self, out: &Output, opt: &Opt) -> Result<()> {
+        let gen = self.displayed_gen(opt);
+        let shared = self.shared.clone();
+        let _output = shared.as_ref().map(|s| s.lock());
+        if out.tui {
+            self.draw_tui(out, opt);
+            return Ok(());
+        }
+        self.end_progress_line();
+        self.display(out, opt, View::Searching, gen);
+        if self.dry_run {
+            return Ok(());
+        }
+        let path = opt.dir.join(PARTIAL_NAME);
+        write(&path, Pattern::from_world(&self.world, gen).rle())?;
+        self.register(&path, Category::Derived)?;
+        println!("Wrote the partial pattern to {}.", path.display());
+        self.log("partial", &path.display().to_string())
+    }
+
+    /// The wall-clock time counted by `--timeout`: since this run of the
+    /// search started, or since the start of the whole batch with
+    /// `--round-robin`.
+    fn timeout_elapsed(&self, opt: &Opt) -> Duration {
+        if opt.round_robin.is_some() {
+            budget::elapsed()
+        } else {
+            self.timer.run_times().wall
+        }
+    }
+
+    /// Searches until a stop condition is met or `cancel` is cancelled,
+    /// or for `turn` views if it is not `None`.
+    fn search<P: AsRef<Path>>(
+        &mut self,
+        out: &Output,
+        opt: &Opt,
+        save: P,
+        turn: Option<u64>,
+        cancel: &CancelToken,
+    ) -> Result<Stop> {
+        let stop = self.search_until_stop(out, opt, save, turn, cancel)?;
+        let description = format!(
+            "{} after {} steps in {}",
+            stop.reason(),
+            self.stats.steps,
+            time::times(self.timer.times())
+        );
+        self.log("stop", &description)?;
+        Ok(stop)
+    }
+
+    fn search_until_stop<P: AsRef<Path>>(
+        &mut self,
+        out: &Output,
+        opt: &Opt,
+        save: P,
+        turn: Option<u64>,
+        cancel: &CancelToken,
+    ) -> Result<Stop> {
+        let timeout = opt.timeout.map(Duration::from_secs);
+        let per_height_timeout = opt.per_height_timeout.map(Duration::from_secs);
+        let mut views = 0;
+        self.set_title(opt);
+        loop {
+            for _ in 0..opt.save_freq {
+                if turn == Some(views) {
+                    self.write_save(&save, opt.save_rotate)?;
+                    return Ok(Stop::Turn);
+                }
+                views += 1;
+                if out.tui {
+                    if let Some(stop) = self.handle_keys(out, opt, &save, cancel)? {
+                        return Ok(stop);
+                    }
+                }
+                if cancel.is_cancelled() {
+                    self.write_save(&save, opt.save_rotate)?;
+                    self.end_progress_line();
+                    println!("Interrupted.");
+                    return Ok(Stop::Interrupted);
+                }
+                if self.shared.as_ref().is_some_and(|s| s.is_stopped()) {
+                    self.write_save(&save, opt.save_rotate)?;
+                    return Ok(Stop::MaxResults);
+                }
+                if let Some(timeout) = timeout {
+                    let elapsed = self.timeout_elapsed(opt);
+                    if elapsed >= timeout {
+                        self.write_save(&save, opt.save_rotate)?;
+                        self.end_progress_line();
+                        println!("Timeout after {}.", time::duration(elapsed));
+                        return Ok(Stop::Timeout);
+                    }
+                }
+                if opt.max_time.is_some_and(|t| budget::elapsed() >= t)
+                    || opt.max_steps.is_some_and(|n| budget::steps() >= n)
+                {
+                    self.write_save(&save, opt.save_rotate)?;
+                    self.end_progress_line();
+                    println!(
+                        "Budget exhausted after {} and {} steps in this run.",
+                        time::duration(budget::elapsed()),
+                        budget::steps()
+                    );
+                    return Ok(Stop::Budget);
+                }
+                // The last view before `--max-steps` may be shorter.
+                let view_freq = match opt.max_steps {
+                    Some(n) => self.view_freq.min(n - budget::steps()),
+                    None => self.view_freq,
+                };
+                let view_start = self.timer.elapsed();
+                let (status, steps) = self.search_view(out, opt, view_freq, cancel)?;
+                self.stats.steps += steps;
+                budget::add_steps(steps);
+                if let Some(interval) = opt.view_interval {
+                    if status == Status::Searching && steps == self.view_freq {
+                        let elapsed = self.timer.elapsed() - view_start;
+                        self.adjust_view_freq(elapsed, Duration::from_millis(interval));
+                    }
+                }
+                if let Some(max_memory) = opt.max_memory {
+                    let resident = memory::resident().unwrap_or(0);
+                    if resident > max_memory {
+                        self.write_save(&save, opt.save_rotate)?;
+                        self.end_progress_line();
+                        println!(
+                            "Memory usage {} exceeds the limit {}.",
+                            memory::format_size(resident),
+                            memory::format_size(max_memory)
+                        );
+                        return Ok(Stop::MaxMemory);
+                    }
+                }
+                match status {
+                    Status::Found => {
+                        self.end_progress_line();
+                        let shared = self.shared.clone();
+                        let _output = shared.as_ref().map(|s| s.lock());
+                        let (min_gen, min_cell_count) = (0..self.world.config().period)
+                            .map(|t| (t, self.world.cell_count_gen(t)))
+                            .min_by_key(|p| p.1)
+                            .unwrap();
+                        // Another thread may have found a smaller one during
+                        // this view.
+                        if self.world.config().reduce_max
+                            && shared
+                                .as_ref()
+                                .is_some_and(|s| s.bound() > 0 && min_cell_count >= s.bound())
+                        {
+                            self.sync_bound();
+                            continue;
+                        }
+                        if opt.exact_period {
+                            let period = self.true_period();
+                            if period < self.world.config().period {
+                                self.stats.subperiod += 1;
+                                if opt.quiet {
+                                    println!(
+                                        "[{}] Skipped {} with {} cells and period {}.",
+                                        time::clock(opt.utc),
+                                        self.target().with_article(),
+                                        min_cell_count,
+                                        period
+                                    );
+                                }
+                                if self.world.config().reduce_max {
+                                    self.world.set_max_cell_count(Some(min_cell_count - 1));
+                                    self.cell_count = min_cell_count;
+                                    if let Some(shared) = &shared {
+                                        shared.lower_bound(min_cell_count);
+                                    }
+                                    self.trace(Event::Bound, None)?;
+                                }
+                                continue;
+                            }
+                        }
+                        // The search goes on without reducing the bound, since
+                        // a wider spaceship may have as many cells.
+                        if opt.min_width.is_some_and(|w| self.pattern_width() < w) {
+                            self.stats.narrow += 1;
+                            self.restore_bound();
+                            continue;
+                        }
+                        if opt.min_cell_count.is_some_and(|min| min_cell_count < min) {
+                            self.stats.small += 1;
+                            self.restore_bound();
+                            continue;
+                        }
+                        self.gen = min_gen;
+                        self.cell_count = min_cell_count;
+                        let apgcode = self
+                            .apgcode()
+                            .unwrap_or_else(|| String::from("no apgcode for this rule"));
+                        if self.best.as_ref().is_none_or(|b| self.cell_count < b.0) {
+                            self.best = Some((self.cell_count, apgcode.clone()));
+                        }
+                        if out.tui {
+                            self.tui.message = Some(format!(
+                                "Found {} with {} cells ({}) at {}.",
+                                self.target().with_article(),
+                                self.cell_count,
+                                apgcode,
+                                time::clock(opt.utc)
+                            ));
+                            self.draw_tui(out, opt);
+                        } else if !opt.quiet {
+                            self.display(out, opt, View::Found, self.gen);
+                            println!("{} ({})", apgcode, time::clock(opt.utc));
+                        }
+                        let filename = if self.canonical.insert(self.canonical(opt)) {
+                            self.write_result(opt)?
+                        } else {
+                            None
+                        };
+                        // A result that is already saved, e.g., re-found right
+                        // after resuming, is not logged again.
+                        if let Some(filename) = &filename {
+                            self.stats.saved += 1;
+                            self.append_result_log(opt, filename)?;
+                            if let Some(command) = &opt.on_found {
+                                let config = self.world.config();
+                                let (dx, dy, _) = self.output_target(opt);
+                                hook::spawn(
+                                    &substitute(command, &self.placeholders(opt, self.stats.saved)),
+                                    vec![
+                                        ("SSS_FILE", filename.display().to_string()),
+                                        ("SSS_CELLS", self.cell_count.to_string()),
+                                        ("SSS_APGCODE", apgcode.clone()),
+                                        ("SSS_PERIOD", config.period.to_string()),
+                                        ("SSS_DX", dx.to_string()),
+                                        ("SSS_DY", dy.to_string()),
+                                        ("SSS_RULE", config.rule_string.clone()),
+                                        (
+                                            "SSS_ELAPSED_SECS",
+                                            self.timer.times().active.as_secs().to_string(),
+                                        ),
+                                    ],
+                                    opt.on_found_stdin.map(|input| match input {
+                                        HookInput::Rle => self.output_pattern(opt, self.gen).rle(),
+                                    }),
+                                );
+                            }
+                        }
+                        let description = format!(
+                            "{} cells ({}) at width {}, height {}: {}",
+                            self.cell_count,
+                            apgcode,
+                            self.world.config().width,
+                            self.world.config().height,
+                            match &filename {
+                                Some(filename) => filename.display().to_string(),
+                                None => String::from("already saved"),
+                            }
+                        );
+                        let event = if filename.is_some() {
+                            "found"
+                        } else {
+                            "duplicate"
+                        };
+                        self.log(event, &description)?;
+                        if opt.verify {
+                            match verify(&self.world) {
+                                Ok(true) => {}
+                                Ok(false) => println!(
+                                    "Warning: the spaceship with {} cells ({}) does not \
+                                     move as expected after one period.",
+                                    self.cell_count, apgcode
+                                ),
+                                Err(e) => println!("Warning: cannot verify the spaceship: {}", e),
+                            }
+                        }
+                        if self.is_stationary() {
+                            println!(
+                                "Warning: the result with {} cells ({}) does not move \
+                                 after a period, so it is actually an oscillator.",
+                                self.cell_count, apgcode
+                            );
+                        }
+                        if opt.quiet {
+                            match filename {
+                                Some(filename) => println!(
+                                    "[{}] Found {} with {} cells ({}): {}",
+                                    time::clock(opt.utc),
+                                    self.target().with_article(),
+                                    self.cell_count,
+                                    apgcode,
+                                    filename.display()
+                                ),
+                                None if self.dry_run => println!(
+                                    "[{}] Found {} with {} cells ({}).",
+                                    time::clock(opt.utc),
+                                    self.target().with_article(),
+                                    self.cell_count,
+                                    apgcode
+                                ),
+                                None => println!(
+                                    "[{}] Found {} with {} cells ({}), \
+                                     which is already saved.",
+                                    time::clock(opt.utc),
+                                    self.target().with_article(),
+                                    self.cell_count,
+                                    apgcode
+                                ),
+                            }
+                        }
+                        self.trace(Event::Found, Some(self.cell_count))?;
+                        if self.world.config().reduce_max {
+                            self.world.set_max_cell_count(Some(self.cell_count - 1));
+                            if let Some(shared) = &shared {
+                                shared.lower_bound(self.cell_count);
+                            }
+                            self.trace(Event::Bound, None)?;
+                        } else {
+                            self.cell_count = self.bound();
+                        }
+                        self.gen = 0;
+                        self.view_times.clear();
+                        self.trend.reset();
+                        self.height_start = self.timer.run_times().wall;
+                        self.last_found = Some(self.timer.elapsed());
+                        self.stats.found += 1;
+                        self.heights_without_result = 0;
+                        self.set_title(opt);
+                        let found = match &shared {
+                            Some(shared) => shared.add_found(),
+                            None => self.stats.found,
+                        };
+                        if opt.stop_after_first || opt.max_results == Some(found) {
+                            if let Some(shared) = &shared {
+                                shared.stop();
+                            }
+                            self.write_save(&save, opt.save_rotate)?;
+                            println!("Found {}.", self.target().count(found));
+                            return Ok(Stop::MaxResults);
+                        }
+                    }
+                    Status::None => {
+                        self.end_progress_line();
+                        let width = self.world.config().width;
+                        if self.grow == Grow::Height
+                            && (opt.width_step == 0 || width >= opt.max_width)
+                        {
+                            self.exhausted.insert(self.world.config().height);
+                        }
+                        if let Some(stop) = self.next_size(opt, &save)? {
+                            return Ok(stop);
+                        }
+                    }
+                    Status::Initial | Status::Searching => {
+                        self.stats.views += 1;
+                        self.record_view();
+                        let steps = self.total_steps();
+                        if let Some(stats_file) = &mut self.stats_file {
+                            let config = self.world.config();
+                            stats_file.write(steps, config.height, config.max_cell_count)?;
+                        }
+                        self.sync_bound();
+                        self.stats.peak = self.stats.peak.max(self.world.cell_count());
+                        if out.tui {
+                            self.draw_tui(out, opt);
+                        } else if out.progress_line {
+                            let shared = self.shared.clone();
+                            let _output = shared.as_ref().map(|s| s.lock());
+                            self.progress_line(out, opt);
+                        } else if !opt.quiet {
+                            let _output = self.shared.as_ref().map(|s| s.lock());
+                            self.display(out, opt, View::Searching, self.displayed_gen(opt));
+                        }
+                        let bound = self.world.config().max_cell_count;
+                        if self.trend.observe(bound, self.world.cell_count()) {
+                            self.end_progress_line();
+                            println!(
+                                "Hint: the bound <={} may be infeasible at this height \
+                                 - consider starting from a larger height.",
+                                bound.unwrap()
+                            );
+                        }
+                        self.gen = (self.gen + 1) % self.world.config().period;
+                        if per_height_timeout
+                            .is_some_and(|t| self.timer.run_times().wall - self.height_start >= t)
+                        {
+                            self.end_progress_line();
+                            println!(
+                                "Warning: skipped height {} after {} without results; \
+                                 some spaceships may be missed.",
+                                self.world.config().height,
+                                time::duration(self.timer.run_times().wall - self.height_start)
+                            );
+                            if let Some(stop) = self.next_size(opt, &save)? {
+                                return Ok(stop);
+                            }
+                        }
+                    }
+                }
+            }
+            self.write_save(&save, opt.save_rotate)?;
+        }
+    }
+}
+
+/// The path of the metrics of the search with this save file, e.g.,
+/// `metrics_P4H1V1.json` for `save_P4H1V1.json`.
+fn metrics_path(save: &Path) -> PathBuf {
+    let name = save.file_name().unwrap_or_default().to_string_lossy();
+    let stem = name
+        .trim_end_matches(".gz")
+        .trim_end_matches(".zst")
+        .trim_end_matches(".json");
+    let name = match stem.strip_prefix("save") {
+        Some(rest) => format!("metrics{}.json", rest),
+        None => format!("metrics_{}.json", stem),
+    };
+    save.with_file_name(name)
+}
+
+/// Appends a suffix to a path, e.g. `save.json` to `save.json.tmp`.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// Formats a number of steps per second with a metric prefix, e.g. `3.4M`.
+fn format_speed(speed: f64) -> String {
+    if speed >= 1e9 {
+        format!("{:.1}G", speed / 1e9)
+    } else if speed >= 1e6 {
+        format!("{:.1}M", speed / 1e6)
+    } else if speed >= 1e3 {
+        format!("{:.1}k", speed / 1e3)
+    } else {
+        format!("{:.0}", speed)
+    }
+}
+
+/// The path of the save file for a target.
+fn save_path(opt: &Opt, target: &Target) -> PathBuf {
+    let save_dir = opt.save_dir.as_ref().unwrap_or(&opt.dir);
+    let name = if opt.all_symmetries {
+        let name = target.save_name(&opt.save_name);
+        let stem = name.trim_end_matches(".json");
+        format!("{}_{:?}.json", stem, opt.symmetry)
+    } else if opt.several_targets() {
+        target.save_name(&opt.save_name)
+    } else {
+        opt.save_name.clone()
+    };
+    let name = match opt.thread {
+        Some(thread) if thread > 0 => {
+            format!("{}_T{}.json", name.trim_end_matches(".json"), thread)
+        }
+        _ => name,
+    };
+    let save = save_dir.join(name);
+    match opt.save_compress {
+        Some(method) => with_suffix(&save, method.suffix()),
+        None => save,
+    }
+}
+
+/// The save file and its backups that exist, including the ones beyond
+/// `--save-rotate`.
+fn existing_saves(opt: &Opt, save: &Path) -> Vec<PathBuf> {
+    let mut saves: Vec<_> = (0..=opt.save_rotate)
+        .map(|i| match i {
+            0 => save.to_owned(),
+            i => with_suffix(save, &format!(".{}", i)),
+        })
+        .filter(|path| path.exists())
+        .collect();
+    saves.extend(stale_saves(opt, save));
+    saves
+}
+
+/// The backups beyond `--save-rotate`, e.g., the ones kept by an earlier
+/// run with a larger `--save-rotate`.
+fn stale_saves(opt: &Opt, save: &Path) -> Vec<PathBuf> {
+    (opt.save_rotate + 1..)
+        .map(|i| with_suffix(save, &format!(".{}", i)))
+        .take_while(|path| path.exists())
+        .collect()
+}
+
+/// Deletes the backups beyond `--save-rotate`, after a confirmation.
+fn prune_saves(opt: &Opt, saves: &[PathBuf]) -> Result<()> {
+    if !saves.is_empty() {
+        println!(
+            "There are older backups of the search status than --save-rotate {} keeps.",
+            opt.save_rotate
+        );
+    }
+    delete_saves(saves, opt.yes)
+}
+
+/// Deletes the save files, after a confirmation.
+fn delete_saves(saves: &[PathBuf], yes: bool) -> Result<()> {
+    confirm_destructive("delete", saves, yes)?;
+    for path in saves {
+        remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Loads or starts the search for a target.
+///
+/// Returns the search and the path of its save file.
+/// If `cancel` is cancelled while the result directory is read, only the
+/// results read so far are known, and the search stops after its first
+/// view.
+fn prepare(opt: &Opt, target: &Target, cancel: &CancelToken) -> Result<(Sss, PathBuf)> {
+    if !opt.dry_run {
+        create_dir_all(&opt.dir)?;
+        let save_dir = opt.save_dir.as_ref().unwrap_or(&opt.dir);
+        create_dir_all(save_dir)?;
+    }
+    if opt.thread.unwrap_or(0) == 0 {
+        if opt.all_symmetries || opt.symmetries.is_some() {
+            println!(
+                "Searching for {} {}s with symmetry {}.",
+                target,
+                target.noun(),
+                opt.symmetry
+            );
+        } else if opt.several_targets() {
+            println!("Searching for {} {}s.", target, target.noun());
+        }
+    }
+    let save = save_path(opt, target);
+    if !opt.dry_run {
+        if let Some(parent) = save.parent() {
+            create_dir_all(parent)?;
+        }
+    }
+    if opt.fresh {
+        delete_saves(&existing_saves(opt, &save), opt.yes)?;
+    } else if !opt.dry_run {
+        prune_saves(opt, &stale_saves(opt, &save))?;
+    }
+    // A save file that cannot be loaded, e.g., a corrupted one or a
+    // compressed one without the `compress` feature, is never overwritten.
+    let (mut sss, event) = match Sss::from_saves(&save, opt.save_rotate) {
+        Ok(sss) => (sss, Event::Resume),
+        Err(_) if existing_saves(opt, &save).is_empty() => (opt.sss(target)?, Event::Start),
+        Err(e) => bail!(
+            "Cannot load the save file {} or its backups: {}. \
+             Use --fresh to delete them and start a new search.",
+            save.display(),
+            e
+        ),
+    };
+    if event == Event::Resume {
+        sss.apply_overrides(opt)?;
+    }
+    if event == Event::Resume && opt.thread.unwrap_or(0) == 0 {
+        println!(
+            "Resuming a search that has taken {}.",
+            time::times(sss.timer.times())
+        );
+    }
+    // After resuming, the rule in the save file is used everywhere,
+    // whatever `--rule` is.
+    let rule = &sss.world.config().rule_string;
+    if event == Event::Resume && sanitize_rule(rule) != sanitize_rule(&opt.rule) {
+        println!(
+            "Warning: the save file uses the rule {}, which differs from {}. \
+             Continuing with {}.",
+            rule, opt.rule, rule
+        );
+    }
+    let config = sss.world.config();
+    if event == Event::Resume && config.search_order != opt.search_order {
+        println!(
+            "Warning: the save file uses the search order {}, which differs from {}. \
+             Continuing with {}.",
+            search_order_name(&config.search_order),
+            search_order_name(&opt.search_order),
+            search_order_name(&config.search_order)
+        );
+    }
+    if event == Event::Resume && config.new_state != opt.new_state {
+        println!(
+            "Warning: the save file uses the new state {}, which differs from {}. \
+             Continuing with {}.",
+            new_state_name(config.new_state),
+            new_state_name(opt.new_state),
+            new_state_name(config.new_state)
+        );
+    }
+    if event == Event::Resume && sss.grow != opt.grow {
+        println!(
+            "Warning: the save file grows the {}, which differs from --grow {}. \
+             Continuing with {}.",
+            sss.grow, opt.grow, sss.grow
+        );
+    }
+    sss.dry_run = opt.dry_run;
+    let scan = !opt.no_dedupe || !opt.no_bound_from_dir;
+    if scan && !sss.world.is_gen_rule() && !sss.world.is_b0_rule() {
+        let rule = sss.world.config().rule_string.clone();
+        let known = dedupe::scan(&opt.dir, &rule, target, cancel)?;
+        if !opt.no_dedupe {
+            sss.canonical.extend(known.canonical);
+        }
+        let inferred = known
+            .min_cell_count
+            .filter(|_| !opt.no_bound_from_dir && sss.world.config().reduce_max)
+            .filter(|&count| opt.min_cell_count.is_none_or(|min| count > min));
+        if let Some(count) = inferred {
+            if sss.bound() == 0 || count < sss.bound() {
+                if opt.thread.unwrap_or(0) == 0 {
+                    println!(
+                        "The smallest spaceship in {} has {} cells; \
+                         searching for fewer cells.",
+                        opt.dir.display(),
+                        count
+                    );
+                }
+                sss.lower_bound(count);
+            }
+        }
+    }
+    if !opt.dry_run {
+        sss.manifest_dir = Some(opt.manifest_dir().to_owned());
+    }
+    if let Some(trace) = &opt.trace {
+        sss.trace = Some(Trace::open(trace)?);
+        manifest::register(opt.manifest_dir(), trace, Category::Log, false)?;
+    }
+    if let Some(event_log) = &opt.event_log_path() {
+        sss.event_log = Some(EventLog::open(event_log)?);
+        manifest::register(opt.manifest_dir(), event_log, Category::Log, false)?;
+    }
+    if let Some(stats_file) = &opt.stats_file {
+        sss.stats_file = Some(StatsFile::open(stats_file)?);
+        manifest::register(opt.manifest_dir(), stats_file, Category::Log, false)?;
+    }
+    sss.view_freq = opt.init_view_freq();
+    sss.trace(event, None)?;
+    let config = sss.world.config();
+    let description = format!(
+        "{} {}s in {}, symmetry {}, width {}, height {}{}",
+        target,
+        target.noun(),
+        config.rule_string,
+        config.symmetry,
+        config.width,
+        config.height,
+        match opt.thread {
+            Some(thread) => format!(", thread {}", thread),
+            None => String::new(),
+        }
+    );
+    match event {
+        Event::Resume => sss.log("resume", &description)?,
+        _ => sss.log("start", &description)?,
+    }
+    Ok((sss, save))
+}
+
+/// Searches for a target with `opt.threads` threads,
+/// each at a different height. Each thread prints its own summary, and
+/// is added to `totals`.
+///
+/// Returns the reason why the first thread stopped, or the one that
+/// aborts the whole run if any.
+fn run_threads(
+    target: &Target,
+    opt: &Opt,
+    out: &Output,
+    cancel: &CancelToken,
+    totals: &mut Totals,
+) -> Result<Stop> {
+    let opts: Vec<_> = (0..opt.threads).map(|i| opt.for_thread(i)).collect();
+    if opt.fresh {
+        let saves: Vec<_> = opts
+            .iter()
+            .flat_map(|opt| existing_saves(opt, &save_path(opt, target)))
+            .collect();
+        delete_saves(&saves, opt.yes)?;
+    } else if !opt.dry_run {
+        let saves: Vec<_> = opts
+            .iter()
+            .flat_map(|opt| stale_saves(opt, &save_path(opt, target)))
+            .collect();
+        prune_saves(opt, &saves)?;
+    }
+    let shared = Arc::new(Shared::default());
+    let barrier = Arc::new(Barrier::new(opt.threads));
+    let handles: Vec<_> = opts
+        .into_iter()
+        .map(|opt| {
+            let target = *target;
+            let out = *out;
+            let shared = shared.clone();
+            let barrier = barrier.clone();
+            let cancel = cancel.clone();
+            thread::spawn(move || -> Result<(Stop, Totals)> {
+                let prepared = prepare(&opt, &target, &cancel);
+                if let Ok((sss, _)) = &prepared {
+                    shared.reserve_height(sss.world.config().height);
+                    shared.lower_bound(sss.bound());
+                }
+                // Waits until the heights and the bounds of all threads are
+                // known, even if this thread fails.
+                barrier.wait();
+                let (mut sss, save) = prepared?;
+                sss.shared = Some(shared.clone());
+                let result = sss.search(&out, &opt, &save, None, &cancel);
+                if result.is_err() {
+                    shared.stop();
+                }
+                let stop = result?;
+                let _output = shared.lock();
+                sss.print_summary(&opt, stop)?;
+                let mut totals = Totals::default();
+                totals.add(&sss);
+                Ok((stop, totals))
+            })
+        })
+        .collect();
+    let mut stops = Vec::new();
+    for handle in handles {
+        match handle.join() {
+            Ok(result) => {
+                let (stop, thread_totals) = result?;
+                stops.push(stop);
+                totals.merge(&thread_totals);
+            }
+            Err(_) => bail!("A search thread panicked."),
+        }
+    }
+    Ok(stops
+        .iter()
+        .copied()
+        .find(|stop| stop.aborts_run())
+        .unwrap_or(stops[0]))
+}
+
+/// Searches for a target at each height of `--heights`, for the time given
+/// by `--survey`, and ranks the heights. The search at each height prints
+/// its own summary, and is added to `totals`.
+///
+/// Returns the reason if the whole run is aborted, or `Stop::MaxSize` after
+/// surveying all the heights.
+fn run_survey(
+    target: &Target,
+    opt: &Opt,
+    out: &Output,
+    cancel: &CancelToken,
+    totals: &mut Totals,
+) -> Result<Stop> {
+    let secs = opt.survey.unwrap_or(0);
+    let (min, max) = opt.heights.unwrap_or((opt.init_height, opt.init_height));
+    create_dir_all(&opt.dir)?;
+    let save_dir = opt.save_dir.as_ref().unwrap_or(&opt.dir);
+    create_dir_all(save_dir)?;
+    let mut reports = Vec::new();
+    let mut aborted = Stop::MaxSize;
+    for height in min..=max {
+        let mut opt = opt.clone();
+        opt.init_height = height;
+        opt.max_height = Some(height);
+        opt.timeout = Some(secs);
+        opt.grow = Grow::Height;
+        opt.save_rotate = 0;
+        println!(
+            "Surveying {} {}s at height {}.",
+            target,
+            target.noun(),
+            height
+        );
+        let save = save_dir.join(format!("survey_H{}.json", height));
+        let mut sss = opt.sss(target)?;
+        // The spaceships found at the previous heights are in the result
+        // directory too. The bound is not inferred from them, since each
+        // height is searched from scratch.
+        if !opt.no_dedupe && !sss.world.is_gen_rule() && !sss.world.is_b0_rule() {
+            let known = dedupe::scan(&opt.dir, &opt.rule, target, cancel)?;
+            sss.canonical.extend(known.canonical);
+        }
+        let stop = sss.search(out, &opt, &save, None, cancel)?;
+        sss.print_summary(&opt, stop)?;
+        totals.add(&sss);
+        reports.push(HeightReport {
+            height,
+            steps: sss.stats.steps,
+            secs: sss.timer.elapsed().as_secs_f64(),
+            peak_cells: sss.stats.peak,
+            near_misses: sss.trend.near_misses,
+            best: sss.best.as_ref().map(|b| b.0),
+            exhausted: stop == Stop::MaxSize,
+        });
+        drop(sss);
+        for path in [&save, &metrics_path(&save)] {
+            if path.exists() {
+                remove_file(path)?;
+            }
+        }
+        if stop.aborts_run() {
+            aborted = stop;
+            break;
+        }
+    }
+    survey::rank(&mut reports);
+    survey::print(&reports);
+    let name = if opt.several_targets() {
+        format!("survey_P{}H{}V{}.json", target.period, target.dx, target.dy)
+    } else {
+        String::from("survey.json")
+    };
+    let path = opt.dir.join(name);
+    survey::write(&path, target, secs, &reports)?;
+    manifest::register(opt.manifest_dir(), &path, Category::Derived, false)?;
+    Ok(aborted)
+}
+
+/// Totals of the searches of a batch, printed after all of them.
+#[derive(Clone, Debug, Default)]
+struct Totals {
+    searches: u32,
+    found: u32,
+    steps: u64,
+    active: Duration,
+    /// Cell count and apgcode of the smallest spaceship in all searches.
+    best: Option<(u32, String)>,
+}
+
+impl Totals {
+    fn add(&mut self, sss: &Sss) {
+        self.searches += 1;
+        self.found += sss.total_found();
+        self.steps += sss.total_steps();
+        self.active += sss.timer.times().active;
+        if let Some(best) = &sss.best {
+            if self.best.as_ref().is_none_or(|b| best.0 < b.0) {
+                self.best = Some(best.clone());
+            }
+        }
+    }
+
+    /// Adds the totals of other searches, e.g., of the threads of a
+    /// parallel search.
+    fn merge(&mut self, other: &Totals) {
+        self.searches += other.searches;
+        self.found += other.found;
+        self.steps += other.steps;
+        self.active += other.active;
+        if let Some(best) = &other.best {
+            if self.best.as_ref().is_none_or(|b| best.0 < b.0) {
+                self.best = Some(best.clone());
+            }
+        }
+    }
+
+    /// Prints the totals, if there is more than one search.
+    fn print(&self) {
+        if self.searches <= 1 {
+            return;
+        }
+        let best = match &self.best {
+            Some((cell_count, apgcode)) => {
+                format!(", the smallest with {} cells ({})", cell_count, apgcode)
+            }
+            None => String::new(),
+        };
+        println!(
+            "In total: {} searches, {} found{}, {} steps in {}.",
+            self.searches,
+            if self.found == 1 {
+                String::from("1 spaceship")
+            } else {
+                format!("{} spaceships", self.found)
+            },
+            best,
+            self.steps,
+            time::duration(self.active)
+        );
+    }
+}
+
+/// Searches for the targets one after another.
+///
+/// Returns the reason if the whole run is aborted,
+/// e.g., by `--max-memory`.
+fn run_sequential(
+    jobs: &[(Target, Opt)],
+    out: &Output,
+    cancel: &CancelToken,
+) -> Result<Option<Stop>> {
+    // The bound reached by the previous symmetry of the same target,
+    // with `--symmetries`.
+    let mut carried: Option<(Target, u32)> = None;
+    let mut totals = Totals::default();
+    for (target, opt) in jobs {
+        let stop = if opt.survey.is_some() {
+            run_survey(target, opt, out, cancel, &mut totals)?
+        } else if opt.threads > 1 {
+            run_threads(target, opt, out, cancel, &mut totals)?
+        } else {
+            let (mut sss, save) = prepare(opt, target, cancel)?;
+            if let Some((_, bound)) = carried.filter(|(t, _)| t == target) {
+                if sss.world.config().reduce_max {
+                    sss.lower_bound(bound);
+                }
+            }
+            let stop = sss.search(out, opt, &save, None, cancel)?;
+            sss.print_summary(opt, stop)?;
+            totals.add(&sss);
+            if opt.symmetries.is_some() {
+                carried = Some((*target, sss.bound()));
+            }
+            stop
+        };
+        if stop.aborts_run() {
+            totals.print();
+            return Ok(Some(stop));
+        }
+    }
+    totals.print();
+    Ok(None)
+}
+
+/// Searches for the targets in turns, each for `turn` views at a time.
+///
+/// Returns the reason if the whole run is aborted,
+/// e.g., by `--max-memory`.
+fn run_round_robin(
+    jobs: &[(Target, Opt)],
+    out: &Output,
+    turn: u64,
+    cancel: &CancelToken,
+) -> Result<Option<Stop>> {
+    let mut searches = Vec::new();
+    for (target, opt) in jobs {
+        let (sss, save) = prepare(opt, target, cancel)?;
+        searches.push((opt, sss, save));
+    }
+    let mut totals = Totals::default();
+    while !searches.is_empty() {
+        let mut i = 0;
+        while i < searches.len() {
+            let (opt, sss, save) = &mut searches[i];
+            match sss.search(out, opt, save, Some(turn), cancel)? {
+                Stop::Turn => i += 1,
+                stop => {
+                    sss.print_summary(opt, stop)?;
+                    totals.add(sss);
+                    if stop.aborts_run() {
+                        totals.print();
+                        return Ok(Some(stop));
+                    }
+                    searches.remove(i);
+                }
+            }
+        }
+    }
+    totals.print();
+    Ok(None)
+}
+
+/// The command line of this run, with the arguments quoted for the shell
+/// when necessary.
+fn command_line() -> String {
+    std::env::args()
+        .map(|arg| {
+            if !arg.is_empty()
+                && arg
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+".contains(c))
+            {
+                arg
+            } else {
+                format!("'{}'", arg.replace('\'', "'\\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn main() -> Result<()> {
+    let matches = Opt::clap().get_matches();
+    let opt = Opt::from_clap(&matches);
+    match &opt.command {
+        Some(Subcommand::Plot(plot)) => plot.run(),
+        Some(Subcommand::Clean(clean)) => clean.run(),
+        Some(Subcommand::Regenerate(regenerate)) => regenerate.run(),
+        None => search(opt, &matches),
+    }
+}
+
+/// Runs the searches given by the options.
+fn search(mut opt: Opt, matches: &ArgMatches) -> Result<()> {
+    opt.read_config_file(matches)?;
+    opt.load_config()?;
+    opt.check_required()?;
+    if let Some(rule) = &opt.override_rule {
+        opt.rule = rule.clone();
+    }
+    if let Some(width) = opt.override_max_width {
+        if width <= 0 {
+            bail!("The overriding width must be positive.");
+        }
+        opt.max_width = width;
+    }
+    check_template(&opt.name_template)?;
+    opt.require_features()?;
+    let mut out = Output::new(opt.color, opt.progress_line);
+    let tui = if opt.tui { tui::enter() } else { None };
+    out.tui = tui.is_some();
+    out.braille = opt.braille;
+    signal::install();
+    budget::start();
+    let mut jobs = opt.skip_small_periods(opt.jobs()?);
+    if jobs.is_empty() {
+        bail!("No search is left after --min-period.");
+    }
+    if opt.all_symmetries {
+        jobs = opt.expand_symmetries(jobs);
+    }
+    if let Some(symmetries) = &opt.symmetries {
+        jobs = opt.with_symmetries(jobs, symmetries);
+    }
+    let jobs: Vec<_> = jobs
+        .iter()
+        .map(|job| (job.target, opt.for_job(job)))
+        .collect();
+    for (target, opt) in &jobs {
+        opt.validate(target)?;
+    }
+    opt.write_effective_config()?;
+    let cancel = CancelToken::on_interrupt();
+    let stop = match opt.round_robin {
+        Some(turn) => run_round_robin(&jobs, &out, turn, &cancel)?,
+        None => run_sequential(&jobs, &out, &cancel)?,
+    };
+    drop(tui);
+    match stop {
+        Some(Stop::MaxMemory) => std::process::exit(EXIT_MAX_MEMORY),
+        Some(Stop::Interrupted) => std::process::exit(EXIT_INTERRUPTED),
+        Some(Stop::Budget) => {
+            println!("Resume with: {}", command_line());
+            std::process::exit(EXIT_BUDGET)
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{read_dir, remove_dir_all};
+
+    /// A search in an empty directory.
+    fn prepare_search(name: &str, args: &[&str]) -> (Sss, Opt, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("spaceships-{}-{}", name, std::process::id()));
+        if dir.exists() {
+            remove_dir_all(&dir).unwrap();
+        }
+        let dir = dir.to_string_lossy().into_owned();
+        let opt = Opt::from_iter(["spaceships", "-d", &dir, "-q"].iter().chain(args));
+        let job = opt.jobs().unwrap().remove(0);
+        let opt = opt.for_job(&job);
+        opt.validate(&job.target).unwrap();
+        let (sss, save) = prepare(&opt, &job.target, &CancelToken::new()).unwrap();
+        (sss, opt, save)
+    }
+
+    /// A search for c/3 spaceships, which takes much more than a few
+    /// thousand steps.
+    const LONG: &[&str] = &["-p", "3", "-x", "1", "-y", "0", "-w", "8", "-h", "8"];
+
+    /// A search for the glider, which is exhausted at height 5.
+    const GLIDER: &[&str] = &[
+        "-p",
+        "4",
+        "-x",
+        "1",
+        "-y",
+        "1",
+        "-w",
+        "5",
+        "--max-height",
+        "5",
+    ];
+
+    fn results(dir: &Path) -> usize {
+        read_dir(dir)
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().path().extension() == Some("rle".as_ref()))
+            .count()
+    }
+
+    /// The options of a command line, with `--config`, if any.
+    fn opt_with_config(args: &[&str]) -> Opt {
+        let matches = Opt::clap().get_matches_from(["spaceships"].iter().chain(args));
+        let mut opt = Opt::from_clap(&matches);
+        opt.read_config_file(&matches).unwrap();
+        opt.check_required().unwrap();
+        opt
+    }
+
+    #[test]
+    fn config_file_precedence() {
+        let path =
+            std::env::temp_dir().join(format!("spaceships-config-{}.toml", std::process::id()));
+        write(
+            &path,
+            "dir = \"from-file\"\nperiod = 4\ndx = 1\ndy = 1\nrule = \"B36/S23\"\nmax_width = 12\n",
+        )
+        .unwrap();
+        let config = path.to_str().unwrap();
+
+        // File only.
+        let opt = opt_with_config(&["--config", config]);
+        assert_eq!(opt.dir, Path::new("from-file"));
+        assert_eq!(opt.period, Some(PeriodRange::single(4)));
+        assert_eq!((opt.dx, opt.dy), (Some(1), Some(1)));
+        assert_eq!(opt.rule, "B36/S23");
+        assert_eq!(opt.max_width, 12);
+        assert_eq!(opt.init_height, 1);
+
+        // Flag only.
+        let opt = opt_with_config(&[
+            "-d",
+            "from-flag",
+            "-p",
+            "3",
+            "-x",
+            "1",
+            "-y",
+            "0",
+            "-w",
+            "7",
+        ]);
+        assert_eq!(opt.dir, Path::new("from-flag"));
+        assert_eq!(opt.rule, "B3/S23");
+        assert_eq!(opt.max_width, 7);
+
+        // Both, where the flags win, even when they are the default values.
+        let opt = opt_with_config(&[
+            "--config",
+            config,
+            "-d",
+            "from-flag",
+            "-p",
+            "3..=5",
+            "-r",
+            "B3/S23",
+            "-w",
+            "1024",
+            "-h",
+            "3",
+        ]);
+        assert_eq!(opt.dir, Path::new("from-flag"));
+        assert_eq!(opt.period, "3..=5".parse().ok());
+        assert_eq!((opt.dx, opt.dy), (Some(1), Some(1)));
+        assert_eq!(opt.rule, "B3/S23");
+        assert_eq!(opt.max_width, 1024);
+        assert_eq!(opt.init_height, 3);
+
+        // The effective config runs the same search again.
+        opt.effective_config().write(&path).unwrap();
+        let again = opt_with_config(&["--config", config]);
+        assert_eq!(
+            toml::to_string(&again.effective_config()).unwrap(),
+            toml::to_string(&opt.effective_config()).unwrap()
+        );
+        remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn cancelled_view_stops_after_a_chunk() {
+        let (mut sss, opt, _) = prepare_search("cancel-view", LONG);
+        let out = Output::new(ColorChoice::Never, false);
+        let cancel = CancelToken::new();
+        let view = sss
+            .search_view(&out, &opt, 5 * CANCEL_STEPS, &cancel)
+            .unwrap();
+        assert_eq!(view, (Status::Searching, 5 * CANCEL_STEPS));
+        cancel.cancel();
+        let view = sss
+            .search_view(&out, &opt, 5 * CANCEL_STEPS, &cancel)
+            .unwrap();
+        assert_eq!(view, (Status::Searching, CANCEL_STEPS));
+        remove_dir_all(&opt.dir).unwrap();
+    }
+
+    #[test]
+    fn cancelled_search_can_be_resumed() {
+        let (mut sss, opt, save) = prepare_search("cancel-search", LONG);
+        let out = Output::new(ColorChoice::Never, false);
+        let cancel = CancelToken::new();
+        let canceller = {
+            let cancel = cancel.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(200));
+                cancel.cancel();
+            })
+        };
+        let stop = sss.search(&out, &opt, &save, None, &cancel).unwrap();
+        canceller.join().unwrap();
+        assert_eq!(stop, Stop::Interrupted);
+        let steps = sss.total_steps();
+        assert!(steps > 0);
+        drop(sss);
+        // The save file is complete, and nothing else is half written.
+        assert!(!with_suffix(&save, ".tmp").exists());
+        assert_eq!(results(&opt.dir), 0);
+        let (sss, _) = prepare(&opt, &opt.jobs().unwrap()[0].target, &CancelToken::new()).unwrap();
+        assert_eq!(sss.total_steps(), steps);
+        remove_dir_all(&opt.dir).unwrap();
+    }
+
+    #[test]
+    fn search_runs_to_the_end_without_cancellation() {
+        let (mut sss, opt, save) = prepare_search("no-cancel", GLIDER);
+        let out = Output::new(ColorChoice::Never, false);
+        let stop = sss
+            .search(&out, &opt, &save, None, &CancelToken::new())
+            .unwrap();
+        assert_eq!(stop, Stop::MaxSize);
+        assert_eq!(results(&opt.dir), 1);
+        remove_dir_all(&opt.dir).unwrap();
+    }
+
+    #[test]
+    fn subcommands_need_no_search_options() {
+        let opt = Opt::from_iter(&["spaceships", "clean", "--derived"]);
+        assert!(matches!(opt.command, Some(Subcommand::Clean(_))));
+        let opt = Opt::from_iter(&["spaceships", "plot", "--trace", "trace.jsonl"]);
+        assert!(matches!(opt.command, Some(Subcommand::Plot(_))));
+        let opt = Opt::from_iter(&["spaceships", "-p", "4", "-x", "1", "-y", "1"]);
+        assert!(opt.command.is_none());
+        assert!(Opt::clap().get_matches_from_safe(["spaceships"]).is_err());
+    }
 }
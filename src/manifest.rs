@@ -0,0 +1,358 @@
+//! The manifest of the files created in the result directory.
+//!
+//! `MANIFEST.json` at the top of the result directory maps the path of each
+//! file, relative to the directory when possible, to its category, the run
+//! that created it, and whether it can be regenerated from the results.
+//! Use `spaceships clean --derived` to delete the files that can, i.e., the
+//! SVG pictures of `--svg` and the phases of `--all-phases`.
+//!
+//! The other files are never deleted: the results log contains the search
+//! times, and the `.toml` files of `--emit-config` the size of the world,
+//! which are not in the result files, and the gallery is only updated when
+//! a result is found. `spaceships regenerate` rebuilds the deleted files
+//! from the RLE results, with the period and the rule in the gallery.
+
+use crate::{
+    confirm::confirm_destructive,
+    gallery,
+    pattern::{Format, Pattern},
+    svg::{self, SvgStyle},
+    time,
+    verify::evolve,
+};
+use anyhow::{anyhow, bail, Result};
+use rlifesrc_lib::{Config, KnownCell};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{create_dir_all, read_to_string, remove_file, rename, write},
+    path::{Path, PathBuf},
+    process,
+    sync::{Mutex, OnceLock},
+};
+use structopt::StructOpt;
+
+/// The name of the manifest in the result directory.
+pub const MANIFEST_NAME: &str = "MANIFEST.json";
+
+/// Serializes the updates of the manifest by the threads of a search.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Categories of the files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Category {
+    /// A result, e.g., an RLE file.
+    PrimaryResult,
+    /// A file computed from the results or the search, e.g., a survey.
+    Derived,
+    /// A save file or its backup.
+    Checkpoint,
+    /// A log of the results or the events.
+    Log,
+}
+
+/// An entry of the manifest.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub category: Category,
+    pub run_id: String,
+    pub regenerable: bool,
+}
+
+type Manifest = BTreeMap<String, FileEntry>;
+
+/// The ID of this run: the start time in UTC and the process ID,
+/// e.g., `20240501T120000Z-1234`.
+pub fn run_id() -> &'static str {
+    static RUN_ID: OnceLock<String> = OnceLock::new();
+    RUN_ID.get_or_init(|| format!("{}-{}", time::compact_timestamp(), process::id()))
+}
+
+fn read(dir: &Path) -> Result<Manifest> {
+    let path = dir.join(MANIFEST_NAME);
+    if !path.exists() {
+        return Ok(Manifest::new());
+    }
+    Ok(serde_json::from_str(&read_to_string(path)?)?)
+}
+
+/// Writes the manifest to a temporary file first, so that it is never
+/// partially written.
+fn write_manifest(dir: &Path, manifest: &Manifest) -> Result<()> {
+    let path = dir.join(MANIFEST_NAME);
+    let tmp = dir.join(format!("{}.tmp", MANIFEST_NAME));
+    write(&tmp, serde_json::to_string_pretty(manifest)? + "\n")?;
+    rename(tmp, path)?;
+    Ok(())
+}
+
+/// The key of a file in the manifest.
+fn key(dir: &Path, path: &Path) -> String {
+    path.strip_prefix(dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Records a file created in this run in the manifest of `dir`.
+///
+/// A file that is already in the manifest keeps its entry, so that the
+/// run ID is the one that created it.
+pub fn register(dir: &Path, path: &Path, category: Category, regenerable: bool) -> Result<()> {
+    let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut manifest = read(dir)?;
+    let key = key(dir, path);
+    if manifest.contains_key(&key) {
+        return Ok(());
+    }
+    manifest.insert(
+        key,
+        FileEntry {
+            category,
+            run_id: run_id().to_owned(),
+            regenerable,
+        },
+    );
+    write_manifest(dir, &manifest)
+}
+
+/// Deletes files listed in the manifest of a result directory.
+#[derive(Clone, Debug, StructOpt)]
+#[structopt(no_version)]
+pub struct CleanOpt {
+    /// The result directory.
+    #[structopt(short, long, default_value = ".")]
+    dir: PathBuf,
+    /// Delete the files that can be regenerated from the results, i.e.,
+    /// the SVG pictures and the phases of the results.
+    #[structopt(long)]
+    derived: bool,
+    /// Do not ask for confirmation.
+    #[structopt(long)]
+    yes: bool,
+}
+
+impl CleanOpt {
+    /// Deletes the files and removes them from the manifest.
+    pub fn run(&self) -> Result<()> {
+        if !self.derived {
+            bail!("Nothing to clean. Use --derived to delete the regenerable files.");
+        }
+        let mut manifest = read(&self.dir)?;
+        let keys: Vec<_> = manifest
+            .iter()
+            .filter(|(_, entry)| entry.regenerable)
+            .map(|(key, _)| key.clone())
+            .collect();
+        let paths: Vec<_> = keys
+            .iter()
+            .map(|key| self.dir.join(key))
+            .filter(|path| path.exists())
+            .collect();
+        confirm_destructive("delete", &paths, self.yes)?;
+        for path in &paths {
+            remove_file(path)?;
+        }
+        for key in &keys {
+            manifest.remove(key);
+        }
+        write_manifest(&self.dir, &manifest)?;
+        println!("Deleted {} files.", paths.len());
+        Ok(())
+    }
+}
+
+/// Rebuilds the files deleted by `spaceships clean --derived` from the
+/// results in the manifest of a result directory.
+///
+/// The options are the same as in the search, so that the files are the
+/// same as the deleted ones. Only RLE results are supported, since the
+/// other formats lack the rule and the states of Generations rules.
+#[derive(Clone, Debug, StructOpt)]
+#[structopt(no_version)]
+pub struct RegenerateOpt {
+    /// The result directory.
+    #[structopt(short, long, default_value = ".")]
+    dir: PathBuf,
+    /// Draw each result in an SVG file next to the result file.
+    #[structopt(long)]
+    svg: bool,
+    /// Width and height of each cell in the SVG files, in pixels.
+    #[structopt(long, default_value = "10")]
+    svg_cell_size: u32,
+    /// Color of the living cells in the SVG files: `#rgb`, `#rrggbb`, or a
+    /// name like `black`.
+    #[structopt(long, default_value = "#000000")]
+    svg_color: String,
+    /// Color of the background in the SVG files, in the same forms as
+    /// `--svg-color`.
+    #[structopt(long, default_value = "#ffffff")]
+    svg_background: String,
+    /// Write every phase of each result to a subdirectory named after the
+    /// result file.
+    #[structopt(long)]
+    all_phases: bool,
+}
+
+impl RegenerateOpt {
+    /// Rebuilds the files and adds them to the manifest.
+    ///
+    /// Results that cannot be read, or are not in the gallery, are skipped
+    /// with a warning.
+    pub fn run(&self) -> Result<()> {
+        if !self.svg && !self.all_phases {
+            bail!("Nothing to regenerate. Use --svg or --all-phases.");
+        }
+        if self.svg {
+            svg::require()?;
+            svg::check_color("--svg-color", &self.svg_color)?;
+            svg::check_color("--svg-background", &self.svg_background)?;
+        }
+        let results: Vec<_> = read(&self.dir)?
+            .into_iter()
+            .filter(|(_, entry)| entry.category == Category::PrimaryResult)
+            .map(|(key, _)| self.dir.join(key))
+            .collect();
+        let mut count = 0;
+        for path in &results {
+            match self.regenerate(path) {
+                Ok(n) => count += n,
+                Err(e) => println!("Warning: cannot regenerate from {}: {}", path.display(), e),
+            }
+        }
+        println!("Regenerated {} files.", count);
+        Ok(())
+    }
+
+    /// Rebuilds the files of a result, and returns their number.
+    fn regenerate(&self, path: &Path) -> Result<usize> {
+        if path.extension().is_none_or(|ext| ext != "rle") {
+            bail!("only RLE results are supported");
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let entry = gallery::entries(path.parent().unwrap_or(&self.dir))?
+            .into_iter()
+            .find(|entry| entry.file == name)
+            .ok_or_else(|| anyhow!("it is not in the gallery"))?;
+        let text = read_to_string(path)?;
+        let cells: Vec<_> = KnownCell::from_rles(text.as_str())?
+            .into_iter()
+            .filter(|cell| cell.coord.2 == 0)
+            .map(|cell| ((cell.coord.0, cell.coord.1), cell.state))
+            .collect();
+        let gen_rule = Config::new(1, 1, 1)
+            .set_rule_string(&entry.rule)
+            .world()?
+            .is_gen_rule();
+        let gens = if self.all_phases { entry.period } else { 1 };
+        let phases: Vec<_> = evolve(&entry.rule, &cells, gens)?
+            .into_iter()
+            .map(|grid| Pattern {
+                offset: (0, 0),
+                rows: grid
+                    .into_iter()
+                    .map(|row| row.into_iter().map(Some).collect())
+                    .collect(),
+                rule: entry.rule.clone(),
+                gen_rule,
+            })
+            .collect();
+        let mut count = 0;
+        if self.svg {
+            let style = SvgStyle {
+                cell_size: self.svg_cell_size,
+                color: self.svg_color.clone(),
+                background: self.svg_background.clone(),
+            };
+            let svg_path = path.with_extension("svg");
+            write(&svg_path, svg::svg(&phases[0], &style)?)?;
+            register(&self.dir, &svg_path, Category::Derived, true)?;
+            count += 1;
+        }
+        if self.all_phases {
+            // The phases have comments if the result has.
+            let comments = text.starts_with("#C ");
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            let dir = path.with_file_name(format!("{}_phases", stem));
+            create_dir_all(&dir)?;
+            for (t, phase) in phases.iter().enumerate() {
+                let phase_path = dir.join(format!("phase_{}.rle", t));
+                let mut phase_text = String::new();
+                if comments {
+                    phase_text += &format!("#C Phase {} of {}\n", t, name);
+                }
+                phase_text += &phase.encode(Format::Rle);
+                write(&phase_path, phase_text)?;
+                register(&self.dir, &phase_path, Category::Derived, true)?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, remove_dir_all};
+
+    /// An empty directory for a test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("spaceships-{}-{}", name, process::id()));
+        if dir.exists() {
+            remove_dir_all(&dir).unwrap();
+        }
+        create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn register_keeps_the_first_entry() {
+        let dir = test_dir("register");
+        let path = dir.join("5P4H1V1.rle");
+        register(&dir, &path, Category::PrimaryResult, false).unwrap();
+        register(&dir, &path, Category::Derived, true).unwrap();
+        let manifest = read(&dir).unwrap();
+        assert_eq!(manifest.len(), 1);
+        let entry = &manifest["5P4H1V1.rle"];
+        assert_eq!(entry.category, Category::PrimaryResult);
+        assert!(!entry.regenerable);
+        assert_eq!(entry.run_id, run_id());
+        remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn clean_deletes_only_the_regenerable_files() {
+        let dir = test_dir("clean");
+        let files = [
+            ("5P4H1V1.rle", Category::PrimaryResult, false),
+            ("5P4H1V1.svg", Category::Derived, true),
+            ("5P4H1V1_phases/phase_0.rle", Category::Derived, true),
+            ("index.html", Category::Derived, false),
+            ("results.jsonl", Category::Log, false),
+            ("save.json", Category::Checkpoint, false),
+        ];
+        create_dir_all(dir.join("5P4H1V1_phases")).unwrap();
+        for &(name, category, regenerable) in &files {
+            write(dir.join(name), "").unwrap();
+            register(&dir, &dir.join(name), category, regenerable).unwrap();
+        }
+        let clean = CleanOpt {
+            dir: dir.clone(),
+            derived: true,
+            yes: true,
+        };
+        clean.run().unwrap();
+        for &(name, _, regenerable) in &files {
+            assert_eq!(dir.join(name).exists(), !regenerable, "{}", name);
+        }
+        let manifest = read(&dir).unwrap();
+        assert!(manifest.values().all(|entry| !entry.regenerable));
+        assert_eq!(manifest.len(), 4);
+        // Nothing is left to delete.
+        clean.run().unwrap();
+        assert_eq!(read(&dir).unwrap().len(), 4);
+        remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,77 @@
+//! The memory usage of this process, for `--max-memory`.
+
+/// Parses a size in bytes, with an optional suffix `K`, `M`, `G`, or `T`,
+/// e.g., `8G`.
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let (number, unit) = match s.char_indices().last() {
+        Some((i, c)) if c.is_ascii_alphabetic() => (&s[..i], c.to_ascii_uppercase()),
+        _ => (s, 'B'),
+    };
+    let shift = match unit {
+        'B' => 0,
+        'K' => 10,
+        'M' => 20,
+        'G' => 30,
+        'T' => 40,
+        _ => return Err(String::from("invalid size unit")),
+    };
+    let number: u64 = number
+        .trim()
+        .parse()
+        .map_err(|_| String::from("invalid size"))?;
+    number
+        .checked_mul(1 << shift)
+        .ok_or_else(|| String::from("size too large"))
+}
+
+/// Formats a size in bytes with a binary prefix, e.g., `1.5G`.
+pub fn format_size(size: u64) -> String {
+    let size = size as f64;
+    if size >= (1u64 << 30) as f64 {
+        format!("{:.1}G", size / (1u64 << 30) as f64)
+    } else if size >= (1u64 << 20) as f64 {
+        format!("{:.1}M", size / (1u64 << 20) as f64)
+    } else if size >= (1u64 << 10) as f64 {
+        format!("{:.1}K", size / (1u64 << 10) as f64)
+    } else {
+        format!("{}B", size)
+    }
+}
+
+/// Resident set size of this process in bytes.
+#[cfg(target_os = "linux")]
+pub fn resident() -> Option<u64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    // Safety: `sysconf` has no side effects.
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    Some(pages * page_size as u64)
+}
+
+/// Peak resident set size of this process in bytes, which is the closest
+/// available measure on this platform.
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn resident() -> Option<u64> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+    // Safety: `getrusage` only writes to the given struct.
+    let usage = unsafe {
+        if libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) != 0 {
+            return None;
+        }
+        usage.assume_init()
+    };
+    // In bytes on macOS, and in kilobytes on other systems.
+    let max_rss = usage.ru_maxrss as u64;
+    if cfg!(target_os = "macos") {
+        Some(max_rss)
+    } else {
+        Some(max_rss * 1024)
+    }
+}
+
+/// Resident set size of this process, which is unavailable on this platform.
+#[cfg(not(unix))]
+pub fn resident() -> Option<u64> {
+    None
+}
@@ -0,0 +1,158 @@
+//! Settings of the output of the search to the terminal.
+
+use ansi_term::{Color, Style};
+use std::{
+    env,
+    io::{stdout, IsTerminal},
+    str::FromStr,
+};
+use term_size::dimensions;
+
+/// When to use colors in the output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Only when the standard output is a terminal,
+    /// and `NO_COLOR` is not set.
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(String::from("invalid color choice")),
+        }
+    }
+}
+
+/// Which generation is displayed during the search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisplayPhase {
+    /// The next generation at each view.
+    Cycle,
+    /// The generation with the fewest living cells.
+    Min,
+    /// Always this generation.
+    Fixed(i32),
+}
+
+impl FromStr for DisplayPhase {
+    type Err = String;
+
+    /// Parses `cycle`, `min`, or `fixed N`, where `N` may also be given
+    /// alone, or as `fixed:N`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || String::from("invalid display phase");
+        match s.trim() {
+            "cycle" => Ok(DisplayPhase::Cycle),
+            "min" => Ok(DisplayPhase::Min),
+            s => {
+                let n = s
+                    .strip_prefix("fixed")
+                    .unwrap_or(s)
+                    .trim_start_matches(|c: char| c == ':' || c.is_whitespace());
+                n.parse()
+                    .ok()
+                    .filter(|&n| n >= 0)
+                    .map(DisplayPhase::Fixed)
+                    .ok_or_else(invalid)
+            }
+        }
+    }
+}
+
+/// What the display of the search shows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View {
+    /// The current state of the search, shown every view.
+    Searching,
+    /// A result.
+    Found,
+}
+
+impl View {
+    fn style(self) -> Style {
+        match self {
+            View::Searching => Color::Green.normal(),
+            View::Found => Style::default(),
+        }
+    }
+}
+
+/// Settings of the output.
+#[derive(Clone, Copy, Debug)]
+pub struct Output {
+    /// Whether to use colors.
+    pub color: bool,
+    /// Width of the terminal, or `None` if the standard output is not a
+    /// terminal, in which case the world is never truncated.
+    pub width: Option<usize>,
+    /// Whether to overwrite a single status line instead of printing the
+    /// world every view.
+    pub progress_line: bool,
+    /// Whether the full-screen display of `--tui` is used.
+    pub tui: bool,
+    /// Whether to draw the world with Braille characters.
+    pub braille: bool,
+}
+
+impl Output {
+    /// Detects the terminal.
+    pub fn new(color: ColorChoice, progress_line: bool) -> Self {
+        let is_terminal = stdout().is_terminal();
+        let width = if is_terminal {
+            Some(dimensions().map_or(80, |(w, _)| w))
+        } else {
+            None
+        };
+        let color = match color {
+            ColorChoice::Auto => is_terminal && env::var_os("NO_COLOR").is_none(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        };
+        Output {
+            color,
+            width,
+            progress_line,
+            tui: false,
+            braille: false,
+        }
+    }
+
+    /// Width of the status line, which is one less than the width of the
+    /// terminal, or 79 if the output is not a terminal.
+    pub fn line_width(&self) -> usize {
+        self.width.unwrap_or(80).max(2) - 1
+    }
+
+    /// Number of cells in a row of the world that fits in the terminal,
+    /// or `None` if the output is not a terminal.
+    pub fn max_world_width(&self) -> Option<i32> {
+        let width = self.width? as i32 - 1;
+        Some(if self.braille { width * 2 } else { width })
+    }
+
+    /// Paints the status line.
+    pub fn paint_info(&self, info: &str) -> String {
+        self.paint(Color::Yellow.normal(), info)
+    }
+
+    /// Paints the world in the style of the view.
+    pub fn paint_world(&self, view: View, world: &str) -> String {
+        self.paint(view.style(), world)
+    }
+
+    fn paint(&self, style: Style, text: &str) -> String {
+        if self.color {
+            style.paint(text).to_string()
+        } else {
+            text.to_owned()
+        }
+    }
+}
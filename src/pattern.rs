@@ -0,0 +1,499 @@
+//! Patterns extracted from the search world, and their file formats.
+
+use crate::symmetry::upward_transforms;
+use rlifesrc_lib::{PolyWorld, State, Transform, ALIVE, DEAD};
+use std::str::FromStr;
+
+/// File formats of the results.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// [RLE](https://conwaylife.com/wiki/Rle).
+    Rle,
+    /// [Plaintext](https://conwaylife.com/wiki/Plaintext).
+    Cells,
+    /// [Life 1.06](https://conwaylife.com/wiki/Life_1.06).
+    Life106,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rle" => Ok(Format::Rle),
+            "cells" | "plaintext" => Ok(Format::Cells),
+            "life106" | "l106" => Ok(Format::Life106),
+            _ => Err(String::from("invalid format")),
+        }
+    }
+}
+
+impl Format {
+    /// The file extension.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Rle => "rle",
+            Format::Cells => "cells",
+            Format::Life106 => "life",
+        }
+    }
+
+    /// The prefix of the comment lines, or `None` if the format has no
+    /// comments.
+    pub fn comment_prefix(self) -> Option<&'static str> {
+        match self {
+            Format::Rle => Some("#C "),
+            Format::Cells => Some("!"),
+            Format::Life106 => None,
+        }
+    }
+}
+
+/// A generation of the search world, trimmed to the bounding box of the
+/// living cells.
+///
+/// For Generations rules, dying cells also count as living.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern {
+    /// The position of the top left corner in the search world.
+    pub offset: (i32, i32),
+    /// The cells, row by row. `None` means an unknown cell.
+    pub rows: Vec<Vec<Option<State>>>,
+    /// The rule string.
+    pub rule: String,
+    /// Whether the rule is a Generations rule.
+    pub gen_rule: bool,
+}
+
+/// The character of a cell in the display of the world.
+///
+/// For Generations rules, states 1 to 26 are `A` to `Z`, and the higher
+/// states are shown by the last digit of their hexadecimal value.
+pub fn display_char(state: Option<State>, gen_rule: bool) -> char {
+    match state {
+        None => '?',
+        Some(DEAD) => '.',
+        Some(_) if !gen_rule => 'o',
+        Some(State(i)) if i <= 26 => (b'A' + i as u8 - 1) as char,
+        Some(State(i)) => std::char::from_digit(i as u32 % 16, 16).unwrap(),
+    }
+}
+
+impl Pattern {
+    /// Extracts the generation `t` from the world.
+    pub fn from_world(world: &PolyWorld, t: i32) -> Self {
+        let config = world.config();
+        let mut bounding_box: Option<(i32, i32, i32, i32)> = None;
+        for y in 0..config.height {
+            for x in 0..config.width {
+                if let Some(State(i)) = world.get_cell_state((x, y, t)) {
+                    if i > 0 {
+                        bounding_box = Some(match bounding_box {
+                            Some((x_min, y_min, x_max, y_max)) => {
+                                (x_min.min(x), y_min.min(y), x_max.max(x), y_max.max(y))
+                            }
+                            None => (x, y, x, y),
+                        });
+                    }
+                }
+            }
+        }
+        let (offset, rows) = match bounding_box {
+            Some((x_min, y_min, x_max, y_max)) => {
+                let rows = (y_min..=y_max)
+                    .map(|y| {
+                        (x_min..=x_max)
+                            .map(|x| world.get_cell_state((x, y, t)))
+                            .collect()
+                    })
+                    .collect();
+                ((x_min, y_min), rows)
+            }
+            None => ((0, 0), Vec::new()),
+        };
+        Pattern {
+            offset,
+            rows,
+            rule: config.rule_string.clone(),
+            gen_rule: world.is_gen_rule(),
+        }
+    }
+
+    /// The pattern rotated or reflected by the transformation.
+    ///
+    /// The offset is kept, as it is the position in the search world.
+    pub fn transform(&self, transform: Transform) -> Self {
+        let (width, height) = (self.width() as i32, self.height() as i32);
+        // The transformations that require a square world swap the axes.
+        let (new_width, new_height) = if transform.require_square_world() {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        let mut rows = vec![vec![Some(DEAD); new_width as usize]; new_height as usize];
+        for (y, row) in self.rows.iter().enumerate() {
+            for (x, &state) in row.iter().enumerate() {
+                let (x, y, _) = transform.act_on((x as i32, y as i32, 0), width, height);
+                rows[y as usize][x as usize] = state;
+            }
+        }
+        Pattern {
+            rows,
+            ..self.clone()
+        }
+    }
+
+    /// The transformation that anchors a pattern moving by `(dx, dy)` to
+    /// the top, i.e., makes it move up, so that its leading edge is row 0
+    /// of the trimmed pattern. Of the possible ones, the one with the
+    /// smallest RLE is chosen.
+    pub fn top_transform(&self, dx: i32, dy: i32) -> Transform {
+        upward_transforms(dx, dy)
+            .into_iter()
+            .min_by_key(|&transform| self.transform(transform).rle())
+            .unwrap_or(Transform::Id)
+    }
+
+    /// Width of the bounding box.
+    pub fn width(&self) -> usize {
+        self.rows.first().map_or(0, Vec::len)
+    }
+
+    /// Height of the bounding box.
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The living cells, row by row.
+    pub fn grid(&self) -> Vec<Vec<bool>> {
+        self.rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|state| matches!(state, Some(State(i)) if *i > 0))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Encodes the pattern in the given format, without comments.
+    pub fn encode(&self, format: Format) -> String {
+        match format {
+            Format::Rle => self.rle(),
+            Format::Cells => self.plaintext(),
+            Format::Life106 => self.life106(),
+        }
+    }
+
+    /// The token of a cell in RLE format.
+    ///
+    /// For Generations rules, states 1 to 24 are `A` to `X`, and the higher
+    /// states have a prefix from `p` to `y`, e.g., `pA` for 25 and `yO` for
+    /// 255, as in Golly.
+    fn rle_token(&self, state: Option<State>) -> String {
+        match state {
+            None => String::from("?"),
+            Some(DEAD) if self.gen_rule => String::from("."),
+            Some(DEAD) => String::from("b"),
+            Some(_) if !self.gen_rule => String::from("o"),
+            Some(State(i)) if i <= 24 => ((b'A' + i as u8 - 1) as char).to_string(),
+            Some(State(i)) => {
+                let i = i - 25;
+                let prefix = (b'p' + (i / 24) as u8) as char;
+                let suffix = (b'A' + (i % 24) as u8) as char;
+                format!("{}{}", prefix, suffix)
+            }
+        }
+    }
+
+    /// The pattern in RLE format, without comments.
+    pub fn rle(&self) -> String {
+        // The run-length encoding works on tokens, since a state may take
+        // two characters.
+        let mut tokens = Vec::new();
+        for row in &self.rows {
+            let mut line: Vec<_> = row.iter().map(|&state| self.rle_token(state)).collect();
+            while line
+                .last()
+                .is_some_and(|t| [".", "b", "?"].contains(&t.as_str()))
+            {
+                line.pop();
+            }
+            tokens.extend(line);
+            tokens.push(String::from("$"));
+        }
+        while tokens.last().is_some_and(|t| t == "$") {
+            tokens.pop();
+        }
+        tokens.push(String::from("!"));
+        let mut rle = format!(
+            "x = {}, y = {}, rule = {}\n",
+            self.width(),
+            self.height(),
+            self.rule
+        );
+        let mut line = String::new();
+        let mut tokens = tokens.iter().peekable();
+        let mut count = 0;
+        while let Some(token) = tokens.next() {
+            count += 1;
+            if Some(&token) != tokens.peek() {
+                let mut run = if count > 1 {
+                    count.to_string()
+                } else {
+                    String::new()
+                };
+                run += token;
+                if line.len() + run.len() <= 70 {
+                    line += &run;
+                } else {
+                    rle += &line;
+                    rle.push('\n');
+                    line = run;
+                }
+                count = 0;
+            }
+        }
+        rle += &line;
+        if line.len() >= 70 {
+            rle.push('\n');
+        }
+        rle
+    }
+
+    /// The pattern in RLE format on a single line, without the header.
+    pub fn rle_line(&self) -> String {
+        self.rle().lines().skip(1).collect()
+    }
+
+    /// The pattern in Plaintext format, without comments.
+    ///
+    /// For Generations rules, all the states other than dead are `O`.
+    pub fn plaintext(&self) -> String {
+        let mut plaintext = String::new();
+        for row in &self.rows {
+            let mut line = String::new();
+            for &state in row {
+                match state {
+                    Some(DEAD) => line.push('.'),
+                    Some(_) => line.push('O'),
+                    None => line.push('?'),
+                }
+            }
+            plaintext += line.trim_end_matches('.');
+            plaintext.push('\n');
+        }
+        plaintext
+    }
+
+    /// The pattern in Life 1.06 format.
+    ///
+    /// The coordinates are relative to the top left corner of the bounding box.
+    /// For Generations rules, only the living cells are written, without the
+    /// dying cells.
+    pub fn life106(&self) -> String {
+        let mut life106 = String::from("#Life 1.06\n");
+        for (y, row) in self.rows.iter().enumerate() {
+            for (x, &state) in row.iter().enumerate() {
+                if state == Some(ALIVE) {
+                    life106 += &format!("{} {}\n", x, y);
+                }
+            }
+        }
+        life106
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symmetry::act_on_vector;
+    use crate::verify::phases;
+    use rlifesrc_lib::{Config, KnownCell, Status};
+
+    /// The first spaceship found with the given search parameters, with
+    /// at most as many cells as an LWSS.
+    fn found(width: i32, height: i32, period: i32, dx: i32, dy: i32) -> PolyWorld {
+        let mut world = Config::new(width, height, period)
+            .set_translate(dx, dy)
+            .set_max_cell_count(9)
+            .world()
+            .unwrap();
+        assert_eq!(world.search(None), Status::Found);
+        world
+    }
+
+    /// The living cells of the pattern, relative to its bounding box.
+    fn living(pattern: &Pattern) -> Vec<(i32, i32)> {
+        let mut living = Vec::new();
+        for (y, row) in pattern.grid().iter().enumerate() {
+            for (x, &alive) in row.iter().enumerate() {
+                if alive {
+                    living.push((x as i32, y as i32));
+                }
+            }
+        }
+        living
+    }
+
+    /// A pattern from rows of cells, where `.` is dead, `?` is unknown, and
+    /// a letter is a state as in the display of the world.
+    fn pattern(rule: &str, gen_rule: bool, rows: &[&str]) -> Pattern {
+        let rows = rows
+            .iter()
+            .map(|row| {
+                row.chars()
+                    .map(|c| match c {
+                        '.' => Some(DEAD),
+                        '?' => None,
+                        'o' => Some(ALIVE),
+                        c => Some(State((c as u8 - b'A' + 1) as usize)),
+                    })
+                    .collect()
+            })
+            .collect();
+        Pattern {
+            offset: (0, 0),
+            rows,
+            rule: rule.to_owned(),
+            gen_rule,
+        }
+    }
+
+    /// The cells which are not dead in an RLE file, read by rlifesrc.
+    fn read_rle(rle: &str) -> Vec<((i32, i32), usize)> {
+        let mut cells: Vec<_> = KnownCell::from_rles(rle)
+            .unwrap()
+            .into_iter()
+            .filter(|cell| cell.state != DEAD)
+            .map(|cell| ((cell.coord.0, cell.coord.1), cell.state.0))
+            .collect();
+        cells.sort();
+        cells
+    }
+
+    /// The cells which are not dead in a pattern.
+    fn cells(pattern: &Pattern) -> Vec<((i32, i32), usize)> {
+        let mut cells = Vec::new();
+        for (y, row) in pattern.rows.iter().enumerate() {
+            for (x, state) in row.iter().enumerate() {
+                if let Some(State(i)) = state {
+                    if *i > 0 {
+                        cells.push(((x as i32, y as i32), *i));
+                    }
+                }
+            }
+        }
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn rle_of_a_glider() {
+        let glider = pattern("B3/S23", false, &[".o.", "..o", "ooo"]);
+        assert_eq!(glider.rle(), "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!");
+        assert_eq!(glider.rle_line(), "bo$2bo$3o!");
+        assert_eq!(read_rle(&glider.rle()), cells(&glider));
+        assert_eq!(glider.plaintext(), ".O\n..O\nOOO\n");
+        assert_eq!(glider.life106(), "#Life 1.06\n1 0\n2 1\n0 2\n1 2\n2 2\n");
+    }
+
+    #[test]
+    fn rle_of_unknown_and_empty_rows() {
+        let pattern = pattern("B3/S23", false, &["o..", "...", "?.o"]);
+        assert_eq!(pattern.rle_line(), "o2$?bo!");
+        let empty = Pattern {
+            rows: Vec::new(),
+            ..pattern
+        };
+        assert_eq!(empty.rle(), "x = 0, y = 0, rule = B3/S23\n!");
+        assert_eq!((empty.width(), empty.height()), (0, 0));
+    }
+
+    #[test]
+    fn rle_of_long_lines() {
+        // Each line of an RLE file has at most 70 characters.
+        let row: String = (0..100)
+            .map(|i| if i % 2 == 0 { 'o' } else { '.' })
+            .collect();
+        let pattern = pattern("B3/S23", false, &[&row, &row]);
+        let rle = pattern.rle();
+        assert!(rle.lines().all(|line| line.len() <= 70));
+        assert_eq!(read_rle(&rle), cells(&pattern));
+    }
+
+    #[test]
+    fn rle_of_many_states() {
+        // States 25 and above have two characters, and the runs are runs of
+        // states, not of characters.
+        let mut pattern = pattern("23/3/30", true, &["AB..X"]);
+        pattern.rows[0].extend([
+            Some(State(25)),
+            Some(State(25)),
+            Some(State(29)),
+            Some(DEAD),
+        ]);
+        assert_eq!(pattern.rle_line(), "AB2.X2pApE!");
+        assert_eq!(read_rle(&pattern.rle()), cells(&pattern));
+        assert_eq!(display_char(Some(State(26)), true), 'Z');
+        assert_eq!(display_char(Some(State(29)), true), 'd');
+        assert_eq!(display_char(Some(State(2)), false), 'o');
+    }
+
+    #[test]
+    fn from_world_trims_to_the_bounding_box() {
+        for t in 0..4 {
+            let pattern = Pattern::from_world(&found(6, 6, 4, 1, 1), t);
+            let grid = pattern.grid();
+            assert_eq!((pattern.width(), pattern.height()), (3, 3));
+            // Living cells on each edge of the bounding box.
+            assert!(grid[0].contains(&true) && grid[2].contains(&true));
+            assert!(grid.iter().any(|row| row[0]) && grid.iter().any(|row| row[2]));
+            assert_eq!(living(&pattern).len(), 5, "{}", pattern.rle_line());
+        }
+    }
+
+    /// The RLEs of all phases of the spaceship, each anchored to the top.
+    fn aligned_phases(world: &PolyWorld, dx: i32, dy: i32) -> Vec<String> {
+        let mut rles: Vec<_> = (0..world.config().period)
+            .map(|t| {
+                let pattern = Pattern::from_world(world, t);
+                pattern.transform(pattern.top_transform(dx, dy)).rle_line()
+            })
+            .collect();
+        rles.sort();
+        rles.dedup();
+        rles
+    }
+
+    #[test]
+    fn top_transform_moves_up() {
+        for &(width, height, dx, dy) in &[(7, 5, 2, 0), (5, 5, 1, 1)] {
+            let pattern = Pattern::from_world(&found(width, height, 4, dx, dy), 0);
+            let mut anchored = Vec::new();
+            // The same phase in all orientations is anchored to the same
+            // pattern, which moves up, and not to the right.
+            for &transform in Transform::ALL.iter() {
+                let pattern = pattern.transform(transform);
+                let (dx, dy) = act_on_vector(transform, (dx, dy));
+                let aligned = pattern.transform(pattern.top_transform(dx, dy));
+                let (_, (mx, my)) = phases("B3/S23", &living(&aligned), 4).unwrap().unwrap();
+                assert!(my < 0 && mx <= 0 && mx >= my, "moves by ({}, {})", mx, my);
+                assert_eq!(aligned.offset, pattern.offset);
+                anchored.push(aligned.rle());
+            }
+            anchored.dedup();
+            assert_eq!(anchored.len(), 1);
+        }
+    }
+
+    #[test]
+    fn top_transform_ignores_the_position() {
+        // The same LWSS at different heights of the world, and thus
+        // different offsets, is anchored to the same phases.
+        assert_eq!(
+            aligned_phases(&found(7, 5, 4, 2, 0), 2, 0),
+            aligned_phases(&found(7, 6, 4, 2, 0), 2, 0)
+        );
+    }
+}
@@ -0,0 +1,42 @@
+//! The process title, which shows the progress of the search in `ps`.
+//!
+//! On the BSDs the whole title is shown in `ps`. Linux only keeps a name of
+//! 15 bytes, shown in `top` and `ps -o comm`, so a shorter title is used
+//! there. On other platforms nothing happens.
+
+/// Sets the process title. `short` is used on platforms which limit
+/// the length of the title.
+#[cfg(any(
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+pub fn set(title: &str, _short: &str) {
+    if let Ok(title) = std::ffi::CString::new(title) {
+        // Safety: the format string and the title are null-terminated strings.
+        unsafe { libc::setproctitle(b"%s\0".as_ptr().cast(), title.as_ptr()) };
+    }
+}
+
+/// Sets the process title. `short` is used on platforms which limit
+/// the length of the title.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn set(_title: &str, short: &str) {
+    if let Ok(short) = std::ffi::CString::new(short) {
+        // Safety: the name is a null-terminated string, which is truncated
+        // to 16 bytes by the kernel.
+        unsafe { libc::prctl(libc::PR_SET_NAME, short.as_ptr()) };
+    }
+}
+
+/// Sets the process title, which is unsupported on this platform.
+#[cfg(not(any(
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "linux",
+    target_os = "android"
+)))]
+pub fn set(_title: &str, _short: &str) {}
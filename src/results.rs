@@ -0,0 +1,86 @@
+//! The log of the search results, in JSON lines or CSV.
+
+use anyhow::Result;
+use serde_json::{Map, Value};
+use std::{fs::OpenOptions, io::Write, path::Path, str::FromStr};
+
+/// The version of the fields in the results log,
+/// to be increased when the fields change.
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// Formats of the results log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One JSON object per line.
+    Jsonl,
+    /// Comma-separated values, with a header line.
+    Csv,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "jsonl" => Ok(LogFormat::Jsonl),
+            "csv" => Ok(LogFormat::Csv),
+            _ => Err(String::from("invalid log format")),
+        }
+    }
+}
+
+impl LogFormat {
+    /// The default filename of the log in the result directory.
+    pub fn default_name(self) -> &'static str {
+        match self {
+            LogFormat::Jsonl => "results.jsonl",
+            LogFormat::Csv => "results.csv",
+        }
+    }
+}
+
+/// Quotes a CSV field if necessary.
+fn csv_field(value: &Value) -> String {
+    let s = match value {
+        Value::Null => return String::new(),
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    };
+    if s.contains(|c| ",\"\n\r".contains(c)) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s
+    }
+}
+
+/// Appends an entry to the log.
+///
+/// The entry is written with a single call and synced to the disk at once,
+/// so that a crash never loses a logged result. The header of a CSV log is
+/// written when the file is empty.
+pub fn append<P: AsRef<Path>>(path: P, format: LogFormat, entry: &[(&str, Value)]) -> Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut text = String::new();
+    match format {
+        LogFormat::Jsonl => {
+            let map: Map<String, Value> = entry
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect();
+            text += &Value::Object(map).to_string();
+        }
+        LogFormat::Csv => {
+            if file.metadata()?.len() == 0 {
+                let header: Vec<_> = entry.iter().map(|(key, _)| *key).collect();
+                text += &header.join(",");
+                text.push('\n');
+            }
+            let row: Vec<_> = entry.iter().map(|(_, value)| csv_field(value)).collect();
+            text += &row.join(",");
+        }
+    }
+    text.push('\n');
+    file.write_all(text.as_bytes())?;
+    file.sync_data()?;
+    Ok(())
+}
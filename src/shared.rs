@@ -0,0 +1,73 @@
+//! The state shared by the threads of a parallel search.
+
+use std::sync::{
+    atomic::{AtomicBool, AtomicI32, AtomicU32, Ordering},
+    Mutex, MutexGuard, PoisonError,
+};
+
+/// The state shared by the threads of a parallel search,
+/// each searching at a different height.
+#[derive(Debug, Default)]
+pub struct Shared {
+    /// The smallest upper bound of the cell count plus 1 among the threads,
+    /// or 0 if there is no bound.
+    bound: AtomicU32,
+    /// The smallest height that no thread has searched yet.
+    next_height: AtomicI32,
+    /// Number of spaceships found by all threads.
+    found: AtomicU32,
+    /// Whether all threads should stop.
+    stop: AtomicBool,
+    /// Held while printing or writing the results, so that the outputs of
+    /// the threads are not mixed up.
+    output: Mutex<()>,
+}
+
+impl Shared {
+    /// The upper bound of the cell count plus 1, or 0 if there is no bound.
+    pub fn bound(&self) -> u32 {
+        self.bound.load(Ordering::SeqCst)
+    }
+
+    /// Lowers the upper bound of the cell count plus 1 to `bound`,
+    /// unless it is already lower. A `bound` of 0 means no bound.
+    pub fn lower_bound(&self, bound: u32) {
+        if bound > 0 {
+            let _ = self
+                .bound
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |old| {
+                    (old == 0 || bound < old).then_some(bound)
+                });
+        }
+    }
+
+    /// Records that a thread is searching at `height`.
+    pub fn reserve_height(&self, height: i32) {
+        self.next_height.fetch_max(height + 1, Ordering::SeqCst);
+    }
+
+    /// Takes the smallest height that no thread has searched yet.
+    pub fn claim_height(&self) -> i32 {
+        self.next_height.fetch_add(1, Ordering::SeqCst)
+    }
+
+    /// Records a new spaceship, and returns the number found so far.
+    pub fn add_found(&self) -> u32 {
+        self.found.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// Tells all threads to stop.
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether all threads should stop.
+    pub fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::SeqCst)
+    }
+
+    /// Locks the output.
+    pub fn lock(&self) -> MutexGuard<'_, ()> {
+        self.output.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
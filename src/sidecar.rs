@@ -0,0 +1,36 @@
+//! The configuration of the search that found a result, written next to
+//! the result file with `--emit-config`, and read back with `--from-config`.
+
+use anyhow::{Context, Result};
+use rlifesrc_lib::Config;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{read_to_string, write},
+    path::Path,
+};
+
+/// The contents of a `.toml` file next to a result.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Sidecar {
+    /// Maximum width of the search, i.e., `--max-width`.
+    pub max_width: i32,
+    /// The configuration of rlifesrc when the result was found,
+    /// with the size of the world at that time.
+    pub config: Config,
+}
+
+impl Sidecar {
+    /// Writes the file.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        // Through a `toml::Value`, which puts the tables after the values.
+        let value = toml::Value::try_from(self)?;
+        write(path, toml::to_string(&value)?)?;
+        Ok(())
+    }
+
+    /// Reads a file.
+    pub fn read(path: &Path) -> Result<Self> {
+        toml::from_str(&read_to_string(path)?)
+            .with_context(|| format!("Invalid config file {}", path.display()))
+    }
+}
@@ -0,0 +1,114 @@
+//! Handling of `Ctrl-C`, which stops the search after saving it.
+//!
+//! Long operations are given a [`CancelToken`], which they check at least
+//! every few seconds, and leave the search status in a state that can be
+//! resumed when it is cancelled.
+//!
+//! `SIGUSR1` asks the search to show and write the current partial
+//! pattern. Signals are only supported on Unix.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Whether `Ctrl-C` has been pressed.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether `SIGUSR1` has been received since the last dump.
+static DUMP: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+extern "C" fn handle(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+#[cfg(unix)]
+extern "C" fn handle_dump(_: libc::c_int) {
+    DUMP.store(true, Ordering::SeqCst);
+}
+
+/// Installs the handlers of `Ctrl-C` and `SIGUSR1`.
+/// Pressing `Ctrl-C` again aborts at once.
+#[cfg(unix)]
+pub fn install() {
+    // Safety: the handlers only write to atomic variables.
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handle as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESETHAND;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(libc::SIGINT, &action, std::ptr::null_mut());
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handle_dump as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(libc::SIGUSR1, &action, std::ptr::null_mut());
+    }
+}
+
+/// Installs the handlers, which are unsupported on this platform.
+#[cfg(not(unix))]
+pub fn install() {}
+
+/// Whether `Ctrl-C` has been pressed.
+fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Asks a long operation to stop. The clones share the same state.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    /// Whether `Ctrl-C` also cancels it.
+    on_interrupt: bool,
+}
+
+impl CancelToken {
+    /// A token that is only cancelled by [`CancelToken::cancel`], for the
+    /// tests.
+    #[cfg(test)]
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    /// A token that is also cancelled when `Ctrl-C` is pressed.
+    pub fn on_interrupt() -> Self {
+        CancelToken {
+            on_interrupt: true,
+            ..CancelToken::default()
+        }
+    }
+
+    /// Cancels the operations that use this token or its clones.
+    #[cfg(test)]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether the operation should stop.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst) || (self.on_interrupt && interrupted())
+    }
+}
+
+/// Whether `SIGUSR1` has been received since the last call.
+pub fn take_dump() -> bool {
+    DUMP.swap(false, Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_token() {
+        let token = CancelToken::new();
+        let clone = token.clone();
+        assert!(!token.is_cancelled());
+        clone.cancel();
+        assert!(token.is_cancelled());
+        // Another token is independent.
+        assert!(!CancelToken::on_interrupt().is_cancelled());
+    }
+}
@@ -0,0 +1,72 @@
+//! Statistics of a search run.
+
+use crate::time;
+use anyhow::Result;
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+/// Counters of a search run, updated in each step of the search.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// Number of spaceships found, including the ones already saved.
+    pub found: u32,
+    /// Number of spaceships saved.
+    pub saved: u32,
+    /// Number of times the height is increased.
+    pub height_increases: u32,
+    /// Number of times the width is increased.
+    pub width_increases: u32,
+    /// Number of results skipped by `--exact-period`.
+    pub subperiod: u32,
+    /// Number of results skipped by `--min-width`.
+    pub narrow: u32,
+    /// Number of results skipped by `--min-cell-count`.
+    pub small: u32,
+    /// Maximum number of decided living cells in the partial results.
+    pub peak: u32,
+    /// Number of views, i.e., calls to `search` that return without a result.
+    pub views: u64,
+    /// Number of search steps in this run.
+    ///
+    /// A call to `search` that ends with a result or with no result counts
+    /// as its whole number of steps, since the exact number is unknown.
+    pub steps: u64,
+}
+
+/// A CSV file with a line per view, for plotting the progress of a long
+/// search.
+///
+/// The fields are the RFC 3339 timestamp in UTC, the total number of steps
+/// including the previous runs, the height, and the upper bound of the cell
+/// count, which is empty if there is no bound.
+pub struct StatsFile {
+    file: File,
+}
+
+impl StatsFile {
+    /// Opens the file in append mode, and writes the header if it is empty.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "timestamp,steps,height,bound")?;
+        }
+        Ok(StatsFile { file })
+    }
+
+    /// Writes a line for a view.
+    pub fn write(&mut self, steps: u64, height: i32, bound: Option<u32>) -> Result<()> {
+        let bound = bound.map(|b| b.to_string()).unwrap_or_default();
+        writeln!(
+            self.file,
+            "{},{},{},{}",
+            time::timestamp(),
+            steps,
+            height,
+            bound
+        )?;
+        Ok(())
+    }
+}
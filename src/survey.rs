@@ -0,0 +1,243 @@
+//! A quick survey of the heights, to find the most promising ones before a
+//! long search.
+//!
+//! Each height is searched from scratch for a fixed time, and the heights are
+//! ranked by what the search achieves in that time.
+
+use crate::target::Target;
+use anyhow::Result;
+use serde_json::json;
+use std::{cmp::Ordering, fs::File, io::Write, path::Path};
+
+/// Parses a range of heights, e.g., `6..20`, which includes both ends.
+pub fn parse_heights(s: &str) -> Result<(i32, i32), String> {
+    let (min, max) = match s.split_once("..") {
+        Some((min, max)) => (min, max.trim_start_matches('=')),
+        None => (s, s),
+    };
+    let parse = |n: &str| {
+        n.trim()
+            .parse::<i32>()
+            .map_err(|_| String::from("invalid height"))
+    };
+    let (min, max) = (parse(min)?, parse(max)?);
+    if min < 1 || min > max {
+        return Err(String::from("invalid range of heights"));
+    }
+    Ok((min, max))
+}
+
+/// What the search achieves at a height.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeightReport {
+    pub height: i32,
+    /// Number of search steps, counted in whole views.
+    pub steps: u64,
+    /// Time spent on this height, in seconds.
+    pub secs: f64,
+    /// Maximum number of decided living cells in the partial results.
+    pub peak_cells: u32,
+    /// Number of times the partial results came near the bound of the cell
+    /// count, since the last result.
+    pub near_misses: u32,
+    /// Cell count of the smallest spaceship found.
+    pub best: Option<u32>,
+    /// Whether no more result can be found at this height.
+    pub exhausted: bool,
+}
+
+impl HeightReport {
+    /// Number of near misses per million steps. A high difficulty means that
+    /// the bound is likely infeasible at this height.
+    pub fn difficulty(&self) -> f64 {
+        if self.steps == 0 {
+            0.0
+        } else {
+            self.near_misses as f64 * 1e6 / self.steps as f64
+        }
+    }
+
+    /// One line describing the report.
+    fn describe(&self) -> String {
+        if let Some(best) = self.best {
+            format!("found a spaceship with {} cells", best)
+        } else if self.exhausted {
+            format!("exhausted in {:.2}s", self.secs)
+        } else {
+            format!(
+                "difficulty {:.2}, at most {} decided living cells, {} steps",
+                self.difficulty(),
+                self.peak_cells,
+                self.steps
+            )
+        }
+    }
+}
+
+/// Sorts the reports from the most promising height to the least.
+///
+/// Heights with results come first, the smallest result first. Then come
+/// the heights which are not exhausted, the lowest difficulty first, and
+/// then the most decided cells first. Exhausted heights come last, since
+/// nothing more can be found there.
+pub fn rank(reports: &mut [HeightReport]) {
+    let class = |r: &HeightReport| match (r.best, r.exhausted) {
+        (Some(_), _) => 0,
+        (None, false) => 1,
+        (None, true) => 2,
+    };
+    reports.sort_by(|a, b| {
+        class(a)
+            .cmp(&class(b))
+            .then(a.best.cmp(&b.best))
+            .then(
+                a.difficulty()
+                    .partial_cmp(&b.difficulty())
+                    .unwrap_or(Ordering::Equal),
+            )
+            .then(b.peak_cells.cmp(&a.peak_cells))
+            .then(a.height.cmp(&b.height))
+    });
+}
+
+/// Prints the ranked reports.
+pub fn print(reports: &[HeightReport]) {
+    println!("Heights from the most promising to the least:");
+    for (i, report) in reports.iter().enumerate() {
+        println!(
+            "{:>3}. height {}: {}",
+            i + 1,
+            report.height,
+            report.describe()
+        );
+    }
+}
+
+/// Writes the ranked reports as JSON.
+pub fn write<P: AsRef<Path>>(
+    path: P,
+    target: &Target,
+    secs_per_height: u64,
+    reports: &[HeightReport],
+) -> Result<()> {
+    let heights: Vec<_> = reports
+        .iter()
+        .map(|r| {
+            json!({
+                "height": r.height,
+                "steps": r.steps,
+                "secs": r.secs,
+                "peak_cells": r.peak_cells,
+                "near_misses": r.near_misses,
+                "difficulty": r.difficulty(),
+                "best_cell_count": r.best,
+                "exhausted": r.exhausted,
+            })
+        })
+        .collect();
+    let survey = json!({
+        "period": target.period,
+        "dx": target.dx,
+        "dy": target.dy,
+        "secs_per_height": secs_per_height,
+        "heights": heights,
+    });
+    let mut file = File::create(path)?;
+    writeln!(file, "{}", serde_json::to_string_pretty(&survey)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(height: i32, near_misses: u32, peak_cells: u32) -> HeightReport {
+        HeightReport {
+            height,
+            steps: 1_000_000,
+            secs: 1.0,
+            peak_cells,
+            near_misses,
+            best: None,
+            exhausted: false,
+        }
+    }
+
+    #[test]
+    fn ranges_of_heights() {
+        assert_eq!(parse_heights("6..20"), Ok((6, 20)));
+        assert_eq!(parse_heights("6..=20"), Ok((6, 20)));
+        assert_eq!(parse_heights("7"), Ok((7, 7)));
+        assert_eq!(parse_heights(" 6 .. 8 "), Ok((6, 8)));
+        assert!(parse_heights("0..3").is_err());
+        assert!(parse_heights("8..6").is_err());
+        assert!(parse_heights("6..").is_err());
+        assert!(parse_heights("six").is_err());
+    }
+
+    #[test]
+    fn difficulty() {
+        assert_eq!(report(5, 3, 10).difficulty(), 3.0);
+        let report = HeightReport {
+            steps: 0,
+            ..report(5, 3, 10)
+        };
+        assert_eq!(report.difficulty(), 0.0);
+    }
+
+    #[test]
+    fn ranking() {
+        let mut reports = vec![
+            HeightReport {
+                exhausted: true,
+                ..report(6, 0, 0)
+            },
+            report(7, 5, 30),
+            report(8, 1, 20),
+            report(9, 1, 25),
+            HeightReport {
+                best: Some(40),
+                ..report(10, 9, 40)
+            },
+            HeightReport {
+                best: Some(35),
+                exhausted: true,
+                ..report(11, 9, 35)
+            },
+            report(12, 1, 25),
+        ];
+        rank(&mut reports);
+        let heights: Vec<_> = reports.iter().map(|r| r.height).collect();
+        assert_eq!(heights, [11, 10, 9, 12, 8, 7, 6]);
+        assert_eq!(reports[0].describe(), "found a spaceship with 35 cells");
+        assert_eq!(
+            reports[4].describe(),
+            "difficulty 1.00, at most 20 decided living cells, 1000000 steps"
+        );
+        assert_eq!(reports[6].describe(), "exhausted in 1.00s");
+    }
+
+    #[test]
+    fn survey_json() -> Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("spaceships-survey-{}.json", std::process::id()));
+        let target = Target {
+            period: 4,
+            dx: 1,
+            dy: 1,
+        };
+        let reports = [HeightReport {
+            best: Some(5),
+            ..report(5, 2, 5)
+        }];
+        write(&path, &target, 10, &reports)?;
+        let survey: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+        assert_eq!(survey["period"], 4);
+        assert_eq!(survey["secs_per_height"], 10);
+        assert_eq!(survey["heights"][0]["height"], 5);
+        assert_eq!(survey["heights"][0]["best_cell_count"], 5);
+        assert_eq!(survey["heights"][0]["difficulty"], 2.0);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
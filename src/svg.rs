@@ -0,0 +1,128 @@
+//! Pictures of the results in SVG.
+//!
+//! This requires the `svg` feature.
+
+use crate::pattern::Pattern;
+use anyhow::{bail, Result};
+#[cfg(feature = "svg")]
+use rlifesrc_lib::State;
+
+/// The size and the colors of the cells.
+#[derive(Clone, Debug)]
+#[cfg_attr(not(feature = "svg"), allow(dead_code))]
+pub struct SvgStyle {
+    /// Width and height of each cell, in pixels.
+    pub cell_size: u32,
+    /// Color of the living cells.
+    pub color: String,
+    /// Color of the background.
+    pub background: String,
+}
+
+/// Fails if this build does not support SVG pictures.
+pub fn require() -> Result<()> {
+    if !cfg!(feature = "svg") {
+        bail!("This build lacks the `svg` feature.")
+    }
+    Ok(())
+}
+
+/// Checks that the color of an option is `#rgb`, `#rrggbb`, or a name
+/// like `black`, so that it is written in the SVG files as it is.
+pub fn check_color(option: &str, color: &str) -> Result<()> {
+    let valid = match color.strip_prefix('#') {
+        Some(hex) => [3, 6].contains(&hex.len()) && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None => !color.is_empty() && color.chars().all(|c| c.is_ascii_alphabetic()),
+    };
+    if !valid {
+        bail!(
+            "Invalid color {} for {}: use #rgb, #rrggbb, or a color name.",
+            color,
+            option
+        );
+    }
+    Ok(())
+}
+
+/// Draws the pattern as a self-contained SVG, with one square per living
+/// cell.
+///
+/// For Generations rules, the dying cells are drawn in the same color, but
+/// more transparent as they decay.
+#[cfg(feature = "svg")]
+pub fn svg(pattern: &Pattern, style: &SvgStyle) -> Result<String> {
+    let size = style.cell_size;
+    let width = pattern.width() as u32 * size;
+    let height = pattern.height() as u32 * size;
+    let max_state = pattern
+        .rows
+        .iter()
+        .flatten()
+        .filter_map(|state| state.map(|State(i)| i))
+        .max()
+        .unwrap_or(1)
+        .max(1);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\">\n\
+         <rect width=\"{w}\" height=\"{h}\" fill=\"{}\"/>\n\
+         <g fill=\"{}\">\n",
+        style.background,
+        style.color,
+        w = width,
+        h = height
+    );
+    for (y, row) in pattern.rows.iter().enumerate() {
+        for (x, state) in row.iter().enumerate() {
+            let i = match state {
+                Some(State(i)) if *i > 0 => *i,
+                _ => continue,
+            };
+            let opacity = if i > 1 {
+                format!(
+                    " fill-opacity=\"{:.3}\"",
+                    (max_state - i + 1) as f64 / max_state as f64
+                )
+            } else {
+                String::new()
+            };
+            svg += &format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\"{}/>\n",
+                x as u32 * size,
+                y as u32 * size,
+                opacity,
+                s = size
+            );
+        }
+    }
+    svg += "</g>\n</svg>\n";
+    Ok(svg)
+}
+
+#[cfg(not(feature = "svg"))]
+pub fn svg(_: &Pattern, _: &SvgStyle) -> Result<String> {
+    require().map(|_| String::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors() {
+        for color in &["#000", "#00ff7F", "black", "RebeccaPurple"] {
+            assert!(check_color("--svg-color", color).is_ok(), "{}", color);
+        }
+        for color in &[
+            "",
+            "#",
+            "#12345",
+            "#ggg",
+            "red\" onload=\"x",
+            "a&b",
+            "light blue",
+        ] {
+            assert!(check_color("--svg-color", color).is_err(), "{}", color);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Compatibility between symmetries and translations, and the canonical
+//! orientation of the results.
+
+use rlifesrc_lib::{Symmetry, Transform};
+
+/// Applies the linear part of a transformation to a translation vector.
+pub fn act_on_vector(transform: Transform, (dx, dy): (i32, i32)) -> (i32, i32) {
+    match transform {
+        Transform::Id => (dx, dy),
+        Transform::Rotate90 => (dy, -dx),
+        Transform::Rotate180 => (-dx, -dy),
+        Transform::Rotate270 => (-dy, dx),
+        Transform::FlipRow => (dx, -dy),
+        Transform::FlipCol => (-dx, dy),
+        Transform::FlipDiag => (dy, dx),
+        Transform::FlipAntidiag => (-dy, -dx),
+    }
+}
+
+/// Whether a spaceship with the given symmetry can move by `(dx, dy)`.
+///
+/// Every generation of the pattern has the symmetry, so each transformation
+/// in the symmetry group must fix the translation vector.
+pub fn is_compatible(symmetry: Symmetry, dx: i32, dy: i32) -> bool {
+    symmetry
+        .members()
+        .into_iter()
+        .all(|transform| act_on_vector(transform, (dx, dy)) == (dx, dy))
+}
+
+/// All symmetries that are compatible with the translation `(dx, dy)`.
+pub fn compatible_symmetries(dx: i32, dy: i32) -> Vec<Symmetry> {
+    Symmetry::ALL
+        .iter()
+        .copied()
+        .filter(|&symmetry| is_compatible(symmetry, dx, dy))
+        .collect()
+}
+
+/// The transformations that move a spaceship with translation `(dx, dy)`
+/// to the canonical orientation, where it moves by `(u, v)` with
+/// `u >= v >= 0`.
+///
+/// There are two of them when `v = 0` or `u = v`, which differ by a
+/// reflection that fixes the translation.
+pub fn canonical_transforms(dx: i32, dy: i32) -> Vec<Transform> {
+    Transform::ALL
+        .iter()
+        .copied()
+        .filter(|&transform| {
+            let (u, v) = act_on_vector(transform, (dx, dy));
+            u >= v && v >= 0
+        })
+        .collect()
+}
+
+/// The transformations that make a spaceship with translation `(dx, dy)`
+/// move up, i.e., by `(u, v)` with `v >= u >= 0`, so that its leading edge
+/// is its top row.
+///
+/// Since a pattern that moves by `(u, v)` in a period is the original one
+/// translated by `(-u, -v)`, this is the transpose of the canonical
+/// orientation.
+pub fn upward_transforms(dx: i32, dy: i32) -> Vec<Transform> {
+    Transform::ALL
+        .iter()
+        .copied()
+        .filter(|&transform| {
+            let (u, v) = act_on_vector(transform, (dx, dy));
+            v >= u && u >= 0
+        })
+        .collect()
+}
+
+/// The symmetry of a pattern with the given symmetry after the
+/// transformation, e.g., `D2|` becomes `D2-` after a rotation by 90°.
+pub fn transform_symmetry(symmetry: Symmetry, transform: Transform) -> Symmetry {
+    Symmetry::generated_by(
+        symmetry
+            .members()
+            .into_iter()
+            .map(|member| transform * member * transform.inverse()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The nine sign patterns of a translation.
+    const SIGNS: [(i32, i32); 9] = [
+        (0, 0),
+        (1, 0),
+        (-1, 0),
+        (0, 1),
+        (0, -1),
+        (1, 1),
+        (-1, -1),
+        (1, -1),
+        (-1, 1),
+    ];
+
+    fn symmetries(names: &[&str]) -> Vec<Symmetry> {
+        names.iter().map(|name| name.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn compatibility_of_all_symmetries() {
+        for &(dx, dy) in &SIGNS {
+            let expected = match (dx, dy) {
+                (0, 0) => symmetries(&[
+                    "C1", "C2", "C4", "D2|", "D2-", "D2\\", "D2/", "D4X", "D4+", "D8",
+                ]),
+                (_, 0) => symmetries(&["C1", "D2-"]),
+                (0, _) => symmetries(&["C1", "D2|"]),
+                _ if dx == dy => symmetries(&["C1", "D2\\"]),
+                _ => symmetries(&["C1", "D2/"]),
+            };
+            for &symmetry in Symmetry::ALL.iter() {
+                assert_eq!(
+                    is_compatible(symmetry, dx, dy),
+                    expected.contains(&symmetry),
+                    "{} with ({}, {})",
+                    symmetry,
+                    dx,
+                    dy
+                );
+                // Only the signs matter, unless the translation is diagonal.
+                assert_eq!(
+                    is_compatible(symmetry, 3 * dx, 3 * dy),
+                    is_compatible(symmetry, dx, dy)
+                );
+            }
+            assert_eq!(compatible_symmetries(dx, dy), expected);
+        }
+        // An oblique translation is only compatible with `C1`.
+        assert_eq!(compatible_symmetries(2, 1), symmetries(&["C1"]));
+        assert_eq!(compatible_symmetries(-1, 2), symmetries(&["C1"]));
+    }
+
+    #[test]
+    fn canonical_and_upward_orientations() {
+        for &(dx, dy) in SIGNS.iter().chain(&[(2, 1), (-1, 2), (1, -3)]) {
+            let canonical = canonical_transforms(dx, dy);
+            let upward = upward_transforms(dx, dy);
+            for &transform in &canonical {
+                let (u, v) = act_on_vector(transform, (dx, dy));
+                assert!(u >= v && v >= 0);
+            }
+            for &transform in &upward {
+                let (u, v) = act_on_vector(transform, (dx, dy));
+                assert!(v >= u && u >= 0);
+            }
+            let expected = match (dx, dy) {
+                (0, 0) => 8,
+                _ if dx == 0 || dy == 0 || dx.abs() == dy.abs() => 2,
+                _ => 1,
+            };
+            assert_eq!(canonical.len(), expected, "({}, {})", dx, dy);
+            assert_eq!(upward.len(), expected, "({}, {})", dx, dy);
+        }
+    }
+
+    #[test]
+    fn transformed_symmetries() {
+        let cases = [
+            ("D2|", Transform::Rotate90, "D2-"),
+            ("D2\\", Transform::Rotate90, "D2/"),
+            ("D2-", Transform::FlipDiag, "D2|"),
+            ("D2|", Transform::FlipCol, "D2|"),
+            ("C4", Transform::FlipRow, "C4"),
+            ("D4+", Transform::Rotate90, "D4+"),
+            ("D4+", Transform::FlipDiag, "D4+"),
+        ];
+        for &(symmetry, transform, expected) in &cases {
+            let symmetry = symmetry.parse().unwrap();
+            assert_eq!(
+                transform_symmetry(symmetry, transform).to_string(),
+                expected
+            );
+        }
+        // The symmetry of a translation is transformed with it.
+        for &transform in Transform::ALL.iter() {
+            for &(dx, dy) in &SIGNS {
+                for &symmetry in &compatible_symmetries(dx, dy) {
+                    let (u, v) = act_on_vector(transform, (dx, dy));
+                    assert!(is_compatible(transform_symmetry(symmetry, transform), u, v));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,447 @@
+//! Search targets, i.e., the period and the translation of the spaceships,
+//! and batch files listing them.
+
+use anyhow::{anyhow, bail, Context, Result};
+use rlifesrc_lib::Symmetry;
+use serde::{de, Deserialize, Deserializer};
+use std::{fmt, fs::read_to_string, ops::RangeInclusive, path::Path, str::FromStr};
+
+/// The period and the translation of the spaceships to search for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Target {
+    pub period: i32,
+    pub dx: i32,
+    pub dy: i32,
+}
+
+impl fmt::Display for Target {
+    /// Writes the speed in lowest terms, followed by the period if it
+    /// differs, e.g., `(1,0)c/4 (period 8)` for a translation of 2 cells in
+    /// 8 generations, or only the period of oscillators, e.g., `p3`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_oscillator() {
+            return write!(f, "p{}", self.period);
+        }
+        let d = gcd(gcd(self.dx, self.dy), self.period).max(1);
+        write!(f, "({},{})c/{}", self.dx / d, self.dy / d, self.period / d)?;
+        if d > 1 {
+            write!(f, " (period {})", self.period)?;
+        }
+        Ok(())
+    }
+}
+
+/// The greatest common divisor of the absolute values.
+fn gcd(a: i32, b: i32) -> i32 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl FromStr for Target {
+    type Err = anyhow::Error;
+
+    /// Parses `period dx dy`, separated by spaces or commas.
+    fn from_str(s: &str) -> Result<Self> {
+        let numbers = s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|n| !n.is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<i32>, _>>()?;
+        match numbers[..] {
+            [period, dx, dy] => Ok(Target { period, dx, dy }),
+            _ => bail!("Expected a period and a translation, e.g., `4 1 1`."),
+        }
+    }
+}
+
+/// A range of periods on the command line, e.g., `4`, `3..=10`, or `3..11`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PeriodRange {
+    pub min: i32,
+    pub max: i32,
+}
+
+impl FromStr for PeriodRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || String::from("invalid period or range of periods");
+        let parse = |n: &str| n.trim().parse::<i32>().map_err(|_| invalid());
+        let (min, max) = if let Some((min, max)) = s.split_once("..=") {
+            (parse(min)?, parse(max)?)
+        } else if let Some((min, max)) = s.split_once("..") {
+            (parse(min)?, parse(max)? - 1)
+        } else {
+            let period = parse(s)?;
+            (period, period)
+        };
+        if min < 1 || min > max {
+            return Err(invalid());
+        }
+        Ok(PeriodRange { min, max })
+    }
+}
+
+impl fmt::Display for PeriodRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_range() {
+            write!(f, "{}..={}", self.min, self.max)
+        } else {
+            write!(f, "{}", self.min)
+        }
+    }
+}
+
+impl PeriodRange {
+    /// A single period.
+    pub fn single(period: i32) -> Self {
+        PeriodRange {
+            min: period,
+            max: period,
+        }
+    }
+
+    /// Whether the range has more than one period.
+    pub fn is_range(self) -> bool {
+        self.min < self.max
+    }
+
+    /// The periods in the range, from the smallest.
+    pub fn periods(self) -> RangeInclusive<i32> {
+        self.min..=self.max
+    }
+}
+
+impl Target {
+    /// Whether the target is an oscillator, i.e., without translation.
+    pub fn is_oscillator(&self) -> bool {
+        self.dx == 0 && self.dy == 0
+    }
+
+    /// What the results are called in the messages.
+    pub fn noun(&self) -> &'static str {
+        if self.is_oscillator() {
+            "oscillator"
+        } else {
+            "spaceship"
+        }
+    }
+
+    /// A number of results, e.g., `1 spaceship` or `2 spaceships`.
+    pub fn count(&self, n: u32) -> String {
+        if n == 1 {
+            format!("1 {}", self.noun())
+        } else {
+            format!("{} {}s", n, self.noun())
+        }
+    }
+
+    /// The same with the indefinite article, e.g., `an oscillator`.
+    pub fn with_article(&self) -> &'static str {
+        if self.is_oscillator() {
+            "an oscillator"
+        } else {
+            "a spaceship"
+        }
+    }
+
+    /// Checks that the period is positive, and that the speed does not
+    /// exceed the speed of light.
+    ///
+    /// In the range-1 Moore neighborhood of the rules supported by rlifesrc,
+    /// a cell only affects its neighbors, so a pattern moves at most one
+    /// cell per generation in each direction. This bounds the orthogonal,
+    /// diagonal, and oblique speeds alike.
+    pub fn check_speed(&self) -> Result<()> {
+        if self.period <= 0 {
+            bail!("The period must be positive.");
+        }
+        if self.dx.abs().max(self.dy.abs()) > self.period {
+            bail!(
+                "{} is faster than the speed of light: the translation cannot \
+                 exceed the period {}.",
+                self,
+                self.period
+            );
+        }
+        Ok(())
+    }
+
+    /// The name of the subdirectory for the results, e.g., `P4H1V1_C1`.
+    pub fn subdir_name(&self, symmetry: Symmetry) -> String {
+        format!("P{}H{}V{}_{:?}", self.period, self.dx, self.dy, symmetry)
+    }
+
+    /// The name of the save file in a batch search, e.g., `save_P4H1V1.json`
+    /// for the name `save.json`.
+    pub fn save_name(&self, name: &str) -> String {
+        let stem = name.strip_suffix(".json").unwrap_or(name);
+        format!("{}_P{}H{}V{}.json", stem, self.period, self.dx, self.dy)
+    }
+}
+
+/// Reads the targets of a batch search, one `period dx dy` per line,
+/// optionally followed by a symmetry.
+///
+/// Targets with a symmetry have their own subdirectories, e.g.,
+/// `P4H1V1_C1`. Empty lines and lines starting with `#` are ignored.
+pub fn read_batch(path: &Path) -> Result<Vec<Job>> {
+    let mut jobs = Vec::new();
+    for (i, line) in read_to_string(path)?.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let context = || format!("Invalid line {} in {}", i + 1, path.display());
+        let fields: Vec<_> = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|n| !n.is_empty())
+            .collect();
+        let (numbers, symmetry) = match fields.len() {
+            4 => (&fields[..3], Some(fields[3])),
+            _ => (&fields[..], None),
+        };
+        let target: Target = numbers.join(" ").parse().with_context(context)?;
+        let symmetry: Option<Symmetry> = match symmetry {
+            Some(symmetry) => Some(
+                symmetry
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid symmetry {}", symmetry))
+                    .with_context(context)?,
+            ),
+            None => None,
+        };
+        jobs.push(Job {
+            target,
+            subdir: symmetry.map(|symmetry| target.subdir_name(symmetry)),
+            params: Params {
+                symmetry,
+                ..Params::default()
+            },
+        });
+    }
+    if jobs.is_empty() {
+        bail!("No targets in {}.", path.display());
+    }
+    Ok(jobs)
+}
+
+/// Parameters of a search in a TOML batch file.
+///
+/// The parameters at the top level of the file are the defaults for all
+/// searches, and the ones omitted in both places are taken from the command
+/// line.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Params {
+    #[serde(default, deserialize_with = "parse_symmetry")]
+    pub symmetry: Option<Symmetry>,
+    pub rule: Option<String>,
+    pub max_width: Option<i32>,
+    pub init_cell_count: Option<u32>,
+    pub init_height: Option<i32>,
+    pub view_freq: Option<u64>,
+    pub save_freq: Option<u64>,
+    pub timeout: Option<u64>,
+    pub max_results: Option<u32>,
+    pub max_height: Option<i32>,
+}
+
+/// Parses the symmetry in the same way as the command line, e.g., `D2|`.
+fn parse_symmetry<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Symmetry>, D::Error> {
+    Option::<String>::deserialize(d)?
+        .map(|s| {
+            s.parse()
+                .map_err(|_| de::Error::custom(format!("invalid symmetry {}", s)))
+        })
+        .transpose()
+}
+
+impl Params {
+    /// Fills the omitted parameters with the defaults.
+    fn or(self, defaults: &Params) -> Params {
+        let defaults = defaults.clone();
+        Params {
+            symmetry: self.symmetry.or(defaults.symmetry),
+            rule: self.rule.or(defaults.rule),
+            max_width: self.max_width.or(defaults.max_width),
+            init_cell_count: self.init_cell_count.or(defaults.init_cell_count),
+            init_height: self.init_height.or(defaults.init_height),
+            view_freq: self.view_freq.or(defaults.view_freq),
+            save_freq: self.save_freq.or(defaults.save_freq),
+            timeout: self.timeout.or(defaults.timeout),
+            max_results: self.max_results.or(defaults.max_results),
+            max_height: self.max_height.or(defaults.max_height),
+        }
+    }
+}
+
+/// A search in a TOML batch file.
+#[derive(Clone, Debug, Deserialize)]
+struct Entry {
+    period: i32,
+    dx: i32,
+    dy: i32,
+    /// Name of the subdirectory for the results.
+    name: Option<String>,
+    #[serde(flatten)]
+    params: Params,
+}
+
+/// A TOML batch file.
+#[derive(Clone, Debug, Deserialize)]
+struct BatchFile {
+    #[serde(flatten)]
+    defaults: Params,
+    search: Vec<Entry>,
+}
+
+/// A search in a batch.
+#[derive(Clone, Debug)]
+pub struct Job {
+    pub target: Target,
+    /// Name of the subdirectory for the results, if any.
+    pub subdir: Option<String>,
+    pub params: Params,
+}
+
+/// Reads a TOML batch file, with an array of tables named `search`.
+///
+/// Each search has its own subdirectory, given by the `name` field,
+/// or named after the velocity and the symmetry, e.g., `P4H1V1_C1`.
+pub fn read_toml_batch(path: &Path, symmetry: Symmetry) -> Result<Vec<Job>> {
+    let BatchFile { defaults, search } = toml::from_str(&read_to_string(path)?)
+        .with_context(|| format!("Invalid batch file {}", path.display()))?;
+    if search.is_empty() {
+        bail!("No searches in {}.", path.display());
+    }
+    Ok(search
+        .into_iter()
+        .map(|entry| {
+            let target = Target {
+                period: entry.period,
+                dx: entry.dx,
+                dy: entry.dy,
+            };
+            let params = entry.params.or(&defaults);
+            let subdir = entry
+                .name
+                .unwrap_or_else(|| target.subdir_name(params.symmetry.unwrap_or(symmetry)));
+            Job {
+                target,
+                subdir: Some(subdir),
+                params,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{remove_file, write};
+
+    fn target(period: i32, dx: i32, dy: i32) -> Target {
+        Target { period, dx, dy }
+    }
+
+    /// Writes a file in the temporary directory, and returns its path.
+    fn temp_file(name: &str, content: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("spaceships-{}-{}", std::process::id(), name));
+        write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn speeds_in_lowest_terms() {
+        assert_eq!(target(4, 1, 1).to_string(), "(1,1)c/4");
+        assert_eq!(target(4, 2, 0).to_string(), "(1,0)c/2 (period 4)");
+        assert_eq!(target(6, -2, 0).to_string(), "(-1,0)c/3 (period 6)");
+        assert_eq!(target(7, 2, 1).to_string(), "(2,1)c/7");
+        assert_eq!(target(8, 2, 2).to_string(), "(1,1)c/4 (period 8)");
+        assert_eq!(target(3, 0, 0).to_string(), "p3");
+    }
+
+    #[test]
+    fn nouns() {
+        assert_eq!(target(4, 1, 1).count(1), "1 spaceship");
+        assert_eq!(target(4, 1, 1).count(0), "0 spaceships");
+        assert_eq!(target(2, 0, 0).count(3), "3 oscillators");
+        assert_eq!(target(2, 0, 0).with_article(), "an oscillator");
+        assert_eq!(target(4, 0, 1).with_article(), "a spaceship");
+    }
+
+    #[test]
+    fn parse_targets_and_periods() {
+        assert_eq!("4 1 1".parse::<Target>().unwrap(), target(4, 1, 1));
+        assert_eq!("4, -2,0".parse::<Target>().unwrap(), target(4, -2, 0));
+        assert!("4 1".parse::<Target>().is_err());
+        assert!("4 1 x".parse::<Target>().is_err());
+        let range = |s: &str| s.parse::<PeriodRange>().map(|r| (r.min, r.max));
+        assert_eq!(range("4"), Ok((4, 4)));
+        assert_eq!(range("3..=10"), Ok((3, 10)));
+        assert_eq!(range("3..11"), Ok((3, 10)));
+        assert!(range("0").is_err());
+        assert!(range("5..=3").is_err());
+        assert!(range("3..3").is_err());
+        assert_eq!(
+            "3..=10".parse::<PeriodRange>().unwrap().to_string(),
+            "3..=10"
+        );
+        assert_eq!(PeriodRange::single(4).to_string(), "4");
+    }
+
+    #[test]
+    fn speed_of_light() {
+        assert!(target(4, 4, 0).check_speed().is_ok());
+        assert!(target(2, 2, -2).check_speed().is_ok());
+        assert!(target(2, 1, -3).check_speed().is_err());
+        assert!(target(0, 0, 0).check_speed().is_err());
+    }
+
+    #[test]
+    fn names() {
+        let symmetry = "D2|".parse().unwrap();
+        assert_eq!(target(4, 0, 1).subdir_name(symmetry), "P4H0V1_D2Col");
+        assert_eq!(target(4, 1, 1).save_name("save.json"), "save_P4H1V1.json");
+        assert_eq!(target(4, 1, 1).save_name("status"), "status_P4H1V1.json");
+    }
+
+    #[test]
+    fn batch_files() {
+        let path = temp_file("batch.txt", "# targets\n4 1 1\n\n3, 1, 0 D2-\n");
+        let jobs = read_batch(&path).unwrap();
+        remove_file(&path).unwrap();
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].target, target(4, 1, 1));
+        assert!(jobs[0].subdir.is_none());
+        assert_eq!(jobs[1].target, target(3, 1, 0));
+        assert_eq!(jobs[1].subdir.as_deref(), Some("P3H1V0_D2Row"));
+        let path = temp_file("bad-batch.txt", "4 1 1\n4 1\n");
+        let error = read_batch(&path).unwrap_err();
+        remove_file(&path).unwrap();
+        assert!(format!("{:#}", error).starts_with("Invalid line 2"));
+    }
+
+    #[test]
+    fn toml_batch_files() {
+        let path = temp_file(
+            "batch.toml",
+            "rule = \"B36/S23\"\nmax_width = 10\n\n\
+             [[search]]\nperiod = 4\ndx = 1\ndy = 1\n\n\
+             [[search]]\nperiod = 3\ndx = 1\ndy = 0\nname = \"c3\"\nmax_width = 12\nsymmetry = \"D2-\"\n",
+        );
+        let jobs = read_toml_batch(&path, Symmetry::C1).unwrap();
+        remove_file(&path).unwrap();
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].subdir.as_deref(), Some("P4H1V1_C1"));
+        assert_eq!(jobs[0].params.rule.as_deref(), Some("B36/S23"));
+        assert_eq!(jobs[0].params.max_width, Some(10));
+        assert_eq!(jobs[1].subdir.as_deref(), Some("c3"));
+        assert_eq!(jobs[1].params.max_width, Some(12));
+        assert_eq!(jobs[1].params.symmetry, Some(Symmetry::D2Row));
+        assert_eq!(jobs[1].params.init_height, None);
+    }
+}
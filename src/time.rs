@@ -0,0 +1,168 @@
+//! Formatting of durations and absolute times, and parsing of durations.
+//!
+//! Machine-readable outputs always use RFC 3339 timestamps in UTC, while
+//! the interactive display uses the local time unless `--utc` is given.
+
+use crate::timer::Times;
+use chrono::{Local, SecondsFormat, Utc};
+use std::time::Duration;
+
+/// Splits a duration, rounded to the nearest second, into days, hours,
+/// minutes, and seconds.
+fn split(duration: Duration) -> (u64, u64, u64, u64) {
+    let secs = (duration + Duration::from_millis(500)).as_secs();
+    (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60)
+}
+
+/// The duration in seconds, rounded to `decimals` decimal places, if it is
+/// still shorter than a minute after rounding.
+fn sub_minute(duration: Duration, decimals: i32) -> Option<f64> {
+    let scale = 10f64.powi(decimals);
+    let secs = (duration.as_secs_f64() * scale).round() / scale;
+    Some(secs).filter(|&secs| secs < 60.0)
+}
+
+/// Formats a duration, e.g., `1d 3h 12m 05s`.
+///
+/// Durations shorter than a minute are written with two decimal places,
+/// e.g., `5.23s`.
+pub fn duration(duration: Duration) -> String {
+    if let Some(secs) = sub_minute(duration, 2) {
+        return format!("{:.2}s", secs);
+    }
+    match split(duration) {
+        (0, 0, m, s) => format!("{}m {:02}s", m, s),
+        (0, h, m, s) => format!("{}h {:02}m {:02}s", h, m, s),
+        (d, h, m, s) => format!("{}d {}h {:02}m {:02}s", d, h, m, s),
+    }
+}
+
+/// Parses a duration with units `d`, `h`, `m`, and `s`, e.g., `23h30m`.
+/// A number without a unit is in seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let invalid = || String::from("invalid duration");
+    let s = s.trim();
+    if let Ok(secs) = s.parse() {
+        return Ok(Duration::from_secs(secs));
+    }
+    let mut secs: u64 = 0;
+    let mut number = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            'd' => 86400,
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        let n: u64 = number.parse().map_err(|_| invalid())?;
+        secs = n
+            .checked_mul(unit)
+            .and_then(|n| secs.checked_add(n))
+            .ok_or_else(invalid)?;
+        number.clear();
+    }
+    if !number.is_empty() || s.is_empty() {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+/// Formats a duration with only the two largest units, e.g., `1d03h`,
+/// for the status line.
+pub fn duration_compact(duration: Duration) -> String {
+    if let Some(secs) = sub_minute(duration, 1) {
+        return format!("{:.1}s", secs);
+    }
+    match split(duration) {
+        (0, 0, m, s) => format!("{}m{:02}s", m, s),
+        (0, h, m, _) => format!("{}h{:02}m", h, m),
+        (d, h, _, _) => format!("{}d{:02}h", d, h),
+    }
+}
+
+/// Formats the time spent on a search, e.g.,
+/// `5m 02s (wall 5m 10s, CPU 5m 01s, suspended 0.00s)`.
+pub fn times(times: Times) -> String {
+    format!(
+        "{} (wall {}, CPU {}, suspended {})",
+        duration(times.active),
+        duration(times.wall),
+        duration(times.cpu),
+        duration(times.suspended)
+    )
+}
+
+/// The current time in RFC 3339 format in UTC, for machine-readable outputs.
+pub fn timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// The current time in UTC without separators, e.g., `20240501T120000Z`,
+/// for filenames.
+pub fn compact_timestamp() -> String {
+    Utc::now().format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// The current time for the interactive display,
+/// in the local time zone unless `utc` is true.
+pub fn clock(utc: bool) -> String {
+    if utc {
+        Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string()
+    } else {
+        Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: f64) -> Duration {
+        Duration::from_secs_f64(secs)
+    }
+
+    #[test]
+    fn duration_minute_boundary() {
+        assert_eq!(duration(secs(5.234)), "5.23s");
+        assert_eq!(duration(secs(59.994)), "59.99s");
+        assert_eq!(duration(secs(59.996)), "1m 00s");
+        assert_eq!(duration(secs(60.0)), "1m 00s");
+        assert_eq!(duration(secs(61.0)), "1m 01s");
+    }
+
+    #[test]
+    fn duration_larger_units() {
+        assert_eq!(duration(secs(3599.6)), "1h 00m 00s");
+        assert_eq!(duration(secs(3723.0)), "1h 02m 03s");
+        assert_eq!(
+            duration(secs(86400.0 + 3.0 * 3600.0 + 12.0 * 60.0 + 5.0)),
+            "1d 3h 12m 05s"
+        );
+    }
+
+    #[test]
+    fn duration_compact_boundaries() {
+        assert_eq!(duration_compact(secs(59.94)), "59.9s");
+        assert_eq!(duration_compact(secs(59.96)), "1m00s");
+        assert_eq!(duration_compact(secs(3600.0 + 120.0)), "1h02m");
+        assert_eq!(duration_compact(secs(86400.0 + 3.0 * 3600.0)), "1d03h");
+    }
+
+    #[test]
+    fn parse_durations() {
+        assert_eq!(parse_duration("90"), Ok(secs(90.0)));
+        assert_eq!(
+            parse_duration("23h30m"),
+            Ok(secs(23.0 * 3600.0 + 30.0 * 60.0))
+        );
+        assert_eq!(parse_duration("1d2s"), Ok(secs(86402.0)));
+        assert!(parse_duration("5x").is_err());
+        assert!(parse_duration("1h30").is_err());
+        assert!(parse_duration("").is_err());
+    }
+}
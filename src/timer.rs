@@ -0,0 +1,295 @@
+//! Accounting of the time spent on a search.
+//!
+//! Only the time inside `world.search` counts as searching. When a slice of
+//! the search takes much longer than the CPU time it used, e.g., because the
+//! process was stopped or the machine was suspended, the difference is
+//! counted as suspended instead.
+
+use anyhow::{bail, Result};
+use std::{
+    fmt,
+    str::FromStr,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// A slice of the search that is off the CPU for longer than this is
+/// considered to be suspended.
+const SUSPEND_THRESHOLD: Duration = Duration::from_secs(5);
+
+/// The time spent on a search.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Times {
+    /// Wall-clock time, including everything between the searches.
+    pub wall: Duration,
+    /// Time spent in the search itself.
+    pub active: Duration,
+    /// CPU time used by the search.
+    pub cpu: Duration,
+    /// Time when the process was stopped or the machine was suspended.
+    pub suspended: Duration,
+}
+
+impl fmt::Display for Times {
+    /// Writes the four durations, e.g., in a save file.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{},{},{},{}",
+            self.wall.as_secs_f64(),
+            self.active.as_secs_f64(),
+            self.cpu.as_secs_f64(),
+            self.suspended.as_secs_f64()
+        )
+    }
+}
+
+impl FromStr for Times {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let secs = s
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<f64>, _>>()?;
+        match secs[..] {
+            [wall, active, cpu, suspended] if secs.iter().all(|s| s.is_finite() && *s >= 0.0) => {
+                Ok(Times {
+                    wall: Duration::from_secs_f64(wall),
+                    active: Duration::from_secs_f64(active),
+                    cpu: Duration::from_secs_f64(cpu),
+                    suspended: Duration::from_secs_f64(suspended),
+                })
+            }
+            _ => bail!("Invalid search times {}.", s),
+        }
+    }
+}
+
+impl Times {
+    /// Adds a slice of the search that took `wall` and used `cpu`, which
+    /// is the same as `wall` if the CPU time is not available.
+    fn add_slice(&mut self, wall: Duration, cpu: Option<Duration>) {
+        let cpu = cpu.map_or(wall, |cpu| cpu.min(wall));
+        let off_cpu = wall - cpu;
+        let suspended = if off_cpu > SUSPEND_THRESHOLD {
+            off_cpu
+        } else {
+            Duration::default()
+        };
+        self.active += wall - suspended;
+        self.cpu += cpu;
+        self.suspended += suspended;
+    }
+
+    /// The durations as seconds, for JSON outputs.
+    pub fn to_json(self) -> serde_json::Value {
+        serde_json::json!({
+            "wall_secs": self.wall.as_secs_f64(),
+            "active_secs": self.active.as_secs_f64(),
+            "cpu_secs": self.cpu.as_secs_f64(),
+            "suspended_secs": self.suspended.as_secs_f64(),
+        })
+    }
+}
+
+/// The CPU time used by the current thread, if it is available.
+#[cfg(unix)]
+fn thread_cpu_time() -> Option<Duration> {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `ts` is a valid pointer to a `timespec`.
+    if unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) } == 0 {
+        Some(Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn thread_cpu_time() -> Option<Duration> {
+    None
+}
+
+/// A slice of the search in progress.
+#[derive(Clone, Copy, Debug)]
+struct Slice {
+    instant: Instant,
+    system: SystemTime,
+    cpu: Option<Duration>,
+}
+
+impl Slice {
+    fn now() -> Self {
+        Slice {
+            instant: Instant::now(),
+            system: SystemTime::now(),
+            cpu: thread_cpu_time(),
+        }
+    }
+
+    /// The wall-clock time since this slice started.
+    ///
+    /// The system clock keeps going while the machine is suspended, but the
+    /// monotonic clock may not, so the larger one of them is used.
+    fn wall(&self) -> Duration {
+        let system = self.system.elapsed().unwrap_or_default();
+        self.instant.elapsed().max(system)
+    }
+}
+
+/// Accumulates the time spent on a search, with slices of the search
+/// marked by [`Timer::start`] and [`Timer::stop`].
+#[derive(Clone, Debug)]
+pub struct Timer {
+    /// Times of the previous runs, loaded from the save file.
+    saved: Times,
+    /// Times of this run, not including the current slice.
+    run: Times,
+    /// The start of this run.
+    run_start: Slice,
+    /// The current slice, if the search is running.
+    slice: Option<Slice>,
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Timer::new(Times::default())
+    }
+}
+
+impl Timer {
+    /// Starts a run, continuing from the times of the previous runs.
+    pub fn new(saved: Times) -> Self {
+        Timer {
+            saved,
+            run: Times::default(),
+            run_start: Slice::now(),
+            slice: None,
+        }
+    }
+
+    /// Starts a slice of the search.
+    pub fn start(&mut self) {
+        if self.slice.is_none() {
+            self.slice = Some(Slice::now());
+        }
+    }
+
+    /// Stops the current slice of the search, if any.
+    pub fn stop(&mut self) {
+        if let Some(slice) = self.slice.take() {
+            let cpu = match (slice.cpu, thread_cpu_time()) {
+                (Some(start), Some(end)) => Some(end.saturating_sub(start)),
+                _ => None,
+            };
+            self.run.add_slice(slice.wall(), cpu);
+        }
+    }
+
+    /// The time spent in the search in this run, including the current
+    /// slice, for the timeouts and the search speed.
+    pub fn elapsed(&self) -> Duration {
+        self.run.active + self.slice.map(|s| s.wall()).unwrap_or_default()
+    }
+
+    /// The time spent in the search in all runs, including the current
+    /// slice, for the status line.
+    pub fn total_elapsed(&self) -> Duration {
+        self.saved.active + self.elapsed()
+    }
+
+    /// The times of all runs of this search, not including the current
+    /// slice.
+    pub fn times(&self) -> Times {
+        Times {
+            wall: self.saved.wall + self.run_start.wall(),
+            active: self.saved.active + self.run.active,
+            cpu: self.saved.cpu + self.run.cpu,
+            suspended: self.saved.suspended + self.run.suspended,
+        }
+    }
+
+    /// The times of this run only.
+    pub fn run_times(&self) -> Times {
+        Times {
+            wall: self.run_start.wall(),
+            ..self.run
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: f64) -> Duration {
+        Duration::from_secs_f64(secs)
+    }
+
+    #[test]
+    fn scripted_slices() {
+        let mut times = Times::default();
+        // Searching on the CPU.
+        times.add_slice(secs(1.0), Some(secs(1.0)));
+        // Waiting for the CPU for a short time, e.g., in nice mode.
+        times.add_slice(secs(2.0), Some(secs(0.5)));
+        // Suspended in the middle of the slice.
+        times.add_slice(secs(100.0), Some(secs(1.0)));
+        // Without the CPU time.
+        times.add_slice(secs(3.0), None);
+        // The CPU time is never more than the wall-clock time.
+        times.add_slice(secs(1.0), Some(secs(1.5)));
+        assert_eq!(times.active, secs(8.0));
+        assert_eq!(times.cpu, secs(6.5));
+        assert_eq!(times.suspended, secs(99.0));
+        assert_eq!(times.wall, Duration::default());
+    }
+
+    #[test]
+    fn times_in_save_files() {
+        let times = Times {
+            wall: secs(10.5),
+            active: secs(8.0),
+            cpu: secs(7.25),
+            suspended: secs(1.0),
+        };
+        assert_eq!(times.to_string(), "10.5,8,7.25,1");
+        assert_eq!(times.to_string().parse::<Times>().unwrap(), times);
+        for invalid in &["1,2,3", "1,2,3,4,5", "1,2,3,-1", "1,2,x,4", "1,2,inf,4", ""] {
+            assert!(invalid.parse::<Times>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn timer_continues_from_the_saved_times() {
+        let saved = Times {
+            wall: secs(100.0),
+            active: secs(60.0),
+            cpu: secs(50.0),
+            suspended: secs(30.0),
+        };
+        let mut timer = Timer::new(saved);
+        assert_eq!(timer.elapsed(), Duration::default());
+        timer.start();
+        std::thread::sleep(Duration::from_millis(20));
+        // The current slice counts in the elapsed time, but not in the times.
+        assert!(timer.elapsed() >= Duration::from_millis(20));
+        assert_eq!(timer.times().active, saved.active);
+        timer.stop();
+        let elapsed = timer.elapsed();
+        assert!(elapsed >= Duration::from_millis(20));
+        assert_eq!(timer.total_elapsed(), saved.active + elapsed);
+        let times = timer.times();
+        assert_eq!(times.active, saved.active + elapsed);
+        assert_eq!(times.suspended, saved.suspended);
+        assert!(times.wall >= saved.wall + elapsed);
+        let run = timer.run_times();
+        assert_eq!(run.active, elapsed);
+        assert!(run.wall >= elapsed && run.wall < times.wall);
+        // Stopping twice does nothing.
+        timer.stop();
+        assert_eq!(timer.elapsed(), elapsed);
+    }
+}
@@ -0,0 +1,264 @@
+//! A light-weight trace of the events during the search, for plotting.
+//!
+//! Each line of the trace file is a JSON object with the following fields:
+//!
+//! * `event`: one of `start`, `resume`, `found`, `bound`, `height`, and
+//!   `width`;
+//! * `wall_secs`: wall-clock time since the start of this run;
+//! * `cpu_secs`: CPU time of this process, if available;
+//! * `steps`: number of search steps since the start of this run,
+//!   counted in whole views;
+//! * `height`: height of the search world;
+//! * `width`: width of the search world;
+//! * `bound`: upper bound of the cell count, if any;
+//! * `cells`: cell count of the result, only for `found` events.
+
+use anyhow::{bail, Result};
+use serde_json::{json, Value};
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use structopt::StructOpt;
+
+/// CPU time used by this process.
+#[cfg(unix)]
+pub fn cpu_time() -> Option<Duration> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+    // Safety: `getrusage` only writes to the given struct.
+    let usage = unsafe {
+        if libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) != 0 {
+            return None;
+        }
+        usage.assume_init()
+    };
+    let time = |t: libc::timeval| Duration::new(t.tv_sec as u64, t.tv_usec as u32 * 1000);
+    Some(time(usage.ru_utime) + time(usage.ru_stime))
+}
+
+/// CPU time used by this process.
+#[cfg(not(unix))]
+pub fn cpu_time() -> Option<Duration> {
+    None
+}
+
+/// An event in the trace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A new search is started.
+    Start,
+    /// A search is resumed from the save file.
+    Resume,
+    /// A result is found.
+    Found,
+    /// The upper bound of the cell count is reduced.
+    Bound,
+    /// The height is increased.
+    Height,
+    /// The width is increased.
+    Width,
+}
+
+impl Event {
+    fn name(self) -> &'static str {
+        match self {
+            Event::Start => "start",
+            Event::Resume => "resume",
+            Event::Found => "found",
+            Event::Bound => "bound",
+            Event::Height => "height",
+            Event::Width => "width",
+        }
+    }
+}
+
+/// The state of the search when an event happens.
+pub struct Snapshot {
+    pub wall: Duration,
+    pub steps: u64,
+    pub height: i32,
+    pub width: i32,
+    pub bound: Option<u32>,
+    pub cells: Option<u32>,
+}
+
+/// The trace file.
+pub struct Trace {
+    file: File,
+}
+
+impl Trace {
+    /// Opens the trace file in append mode.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Trace { file })
+    }
+
+    /// Writes an event to the trace file.
+    pub fn write(&mut self, event: Event, snapshot: &Snapshot) -> Result<()> {
+        let mut line = json!({
+            "event": event.name(),
+            "wall_secs": snapshot.wall.as_secs_f64(),
+            "cpu_secs": cpu_time().map(|t| t.as_secs_f64()),
+            "steps": snapshot.steps,
+            "height": snapshot.height,
+            "width": snapshot.width,
+            "bound": snapshot.bound,
+        });
+        if let Some(cells) = snapshot.cells {
+            line["cells"] = json!(cells);
+        }
+        let mut line = line.to_string();
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+/// Columns of the CSV output of `spaceships plot`.
+const COLUMNS: [&str; 8] = [
+    "event",
+    "wall_secs",
+    "cpu_secs",
+    "steps",
+    "height",
+    "width",
+    "bound",
+    "cells",
+];
+
+/// Converts a trace file into a table for plotting.
+#[derive(Clone, Debug, StructOpt)]
+#[structopt(no_version)]
+pub struct PlotOpt {
+    /// The trace file written by `--trace`.
+    #[structopt(long)]
+    trace: PathBuf,
+    /// Output format. Currently only `csv` is supported.
+    #[structopt(long, default_value = "csv")]
+    format: String,
+}
+
+impl PlotOpt {
+    /// Prints the trace as a table.
+    pub fn run(&self) -> Result<()> {
+        if self.format != "csv" {
+            bail!("Unsupported format: {}", self.format);
+        }
+        let stdout = std::io::stdout();
+        write_csv(BufReader::new(File::open(&self.trace)?), stdout.lock())
+    }
+}
+
+/// Converts the lines of a trace file into CSV rows.
+fn write_csv<R: BufRead, W: Write>(input: R, mut output: W) -> Result<()> {
+    writeln!(output, "{}", COLUMNS.join(","))?;
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: Value = serde_json::from_str(&line)?;
+        let row: Vec<String> = COLUMNS
+            .iter()
+            .map(|&column| match &value[column] {
+                Value::Null => String::new(),
+                Value::String(s) => s.clone(),
+                v => v.to_string(),
+            })
+            .collect();
+        writeln!(output, "{}", row.join(","))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs::remove_file, process};
+
+    fn snapshot(secs: u64, height: i32, bound: Option<u32>, cells: Option<u32>) -> Snapshot {
+        Snapshot {
+            wall: Duration::from_secs(secs),
+            steps: secs * 1000,
+            height,
+            width: 8,
+            bound,
+            cells,
+        }
+    }
+
+    #[test]
+    fn scripted_events() -> Result<()> {
+        let path = env::temp_dir().join(format!("spaceships-trace-{}.jsonl", process::id()));
+        let events = [
+            (Event::Start, snapshot(0, 5, None, None)),
+            (Event::Found, snapshot(1, 5, None, Some(12))),
+            (Event::Bound, snapshot(1, 5, Some(11), None)),
+            (Event::Height, snapshot(3, 6, Some(11), None)),
+            (Event::Width, snapshot(4, 6, Some(11), None)),
+        ];
+        let mut trace = Trace::open(&path)?;
+        for (event, snapshot) in &events[..3] {
+            trace.write(*event, snapshot)?;
+        }
+        // Lines are flushed immediately.
+        let lines = std::fs::read_to_string(&path)?.lines().count();
+        assert_eq!(lines, 3);
+        // A resumed search appends to the same file.
+        drop(trace);
+        let mut trace = Trace::open(&path)?;
+        trace.write(Event::Resume, &snapshot(0, 5, Some(11), None))?;
+        for (event, snapshot) in &events[3..] {
+            trace.write(*event, snapshot)?;
+        }
+
+        let lines: Vec<Value> = BufReader::new(File::open(&path)?)
+            .lines()
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect::<Result<_>>()?;
+        let names: Vec<_> = lines.iter().map(|line| line["event"].clone()).collect();
+        assert_eq!(
+            names,
+            ["start", "found", "bound", "resume", "height", "width"]
+        );
+        assert_eq!(lines[1]["cells"], 12);
+        assert_eq!(lines[1]["bound"], Value::Null);
+        assert_eq!(lines[2]["cells"], Value::Null);
+        assert_eq!(lines[2]["bound"], 11);
+        assert_eq!(lines[4]["height"], 6);
+        assert_eq!(lines[4]["wall_secs"], 3.0);
+        assert_eq!(lines[4]["steps"], 3000);
+        assert_eq!(lines[4]["cpu_secs"].is_number(), cpu_time().is_some());
+
+        let mut csv = Vec::new();
+        write_csv(BufReader::new(File::open(&path)?), &mut csv)?;
+        let csv = String::from_utf8(csv)?;
+        let rows: Vec<_> = csv.lines().collect();
+        assert_eq!(rows.len(), 7);
+        assert_eq!(
+            rows[0],
+            "event,wall_secs,cpu_secs,steps,height,width,bound,cells"
+        );
+        let found: Vec<_> = rows[2].split(',').collect();
+        assert_eq!(found[0], "found");
+        assert_eq!(found[1], "1.0");
+        assert_eq!(&found[3..], ["1000", "5", "8", "", "12"]);
+
+        remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_traces() {
+        let mut csv = Vec::new();
+        assert!(write_csv(&b"{\"event\":\"start\"}\n\nnot json\n"[..], &mut csv).is_err());
+        assert_eq!(
+            String::from_utf8(csv).unwrap().lines().nth(1),
+            Some("start,,,,,,,")
+        );
+    }
+}
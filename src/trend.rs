@@ -0,0 +1,132 @@
+//! Tracks how close partial results get to the cell count bound.
+//!
+//! When the bound is too tight, the number of decided living cells in the
+//! partial result repeatedly approaches the bound and then collapses.
+
+/// A partial result is a near miss if its population is within this
+/// number of cells of the bound.
+const NEAR_MISS_MARGIN: u32 = 2;
+
+/// Number of near misses before suggesting that the bound may be infeasible.
+const NEAR_MISS_THRESHOLD: u32 = 20;
+
+/// Population trend of the partial results since the last bound change.
+#[derive(Clone, Debug, Default)]
+pub struct Trend {
+    bound: Option<u32>,
+    /// Maximum population reached.
+    pub peak: u32,
+    /// Number of times the population has come near the bound.
+    pub near_misses: u32,
+    was_near: bool,
+    hinted: bool,
+}
+
+impl Trend {
+    /// Forgets the history, e.g. when the bound or the height changes.
+    pub fn reset(&mut self) {
+        *self = Trend::default();
+    }
+
+    /// Records the population of the current partial result.
+    ///
+    /// Returns `true` when the hint should be printed,
+    /// which happens at most once until the next reset.
+    pub fn observe(&mut self, bound: Option<u32>, population: u32) -> bool {
+        if bound != self.bound {
+            self.reset();
+            self.bound = bound;
+        }
+        let bound = match bound {
+            Some(bound) => bound,
+            None => return false,
+        };
+        self.peak = self.peak.max(population);
+        let near = population + NEAR_MISS_MARGIN >= bound;
+        if near && !self.was_near {
+            self.near_misses += 1;
+        }
+        self.was_near = near;
+        if self.near_misses >= NEAR_MISS_THRESHOLD && !self.hinted {
+            self.hinted = true;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The bound the trend is tracked against.
+    pub fn bound(&self) -> Option<u32> {
+        self.bound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Observes a sequence of populations, returning the indices of the hints.
+    fn hints(trend: &mut Trend, bound: Option<u32>, populations: &[u32]) -> Vec<usize> {
+        populations
+            .iter()
+            .enumerate()
+            .filter(|&(_, &population)| trend.observe(bound, population))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    #[test]
+    fn collapsing_near_the_bound() {
+        let mut trend = Trend::default();
+        let populations: Vec<u32> = (0..30).flat_map(|_| vec![10, 30, 44, 12]).collect();
+        assert_eq!(hints(&mut trend, Some(45), &populations), vec![19 * 4 + 2]);
+        assert_eq!(trend.peak, 44);
+        assert_eq!(trend.near_misses, 30);
+        assert_eq!(trend.bound(), Some(45));
+    }
+
+    #[test]
+    fn staying_near_the_bound_counts_once() {
+        let mut trend = Trend::default();
+        assert!(hints(&mut trend, Some(45), &[43; 100]).is_empty());
+        assert_eq!(trend.near_misses, 1);
+        assert_eq!(trend.peak, 43);
+    }
+
+    #[test]
+    fn far_from_the_bound() {
+        let mut trend = Trend::default();
+        let populations: Vec<u32> = (0..30).flat_map(|_| vec![10, 42, 12]).collect();
+        assert!(hints(&mut trend, Some(45), &populations).is_empty());
+        assert_eq!(trend.peak, 42);
+        assert_eq!(trend.near_misses, 0);
+    }
+
+    #[test]
+    fn without_a_bound() {
+        let mut trend = Trend::default();
+        let populations: Vec<u32> = (0..30).flat_map(|_| vec![0, 100]).collect();
+        assert!(hints(&mut trend, None, &populations).is_empty());
+        assert_eq!(trend.peak, 0);
+        assert_eq!(trend.near_misses, 0);
+    }
+
+    #[test]
+    fn changing_the_bound_resets() {
+        let mut trend = Trend::default();
+        let populations: Vec<u32> = (0..19).flat_map(|_| vec![0, 20]).collect();
+        assert!(hints(&mut trend, Some(20), &populations).is_empty());
+        assert_eq!(trend.near_misses, 19);
+        assert!(hints(&mut trend, Some(21), &[0, 20]).is_empty());
+        assert_eq!(trend.near_misses, 1);
+        assert_eq!(trend.bound(), Some(21));
+
+        // After a reset, the hint can be printed again.
+        let populations: Vec<u32> = (0..20).flat_map(|_| vec![0, 20]).collect();
+        assert_eq!(hints(&mut trend, Some(20), &populations), vec![39]);
+        trend.reset();
+        assert_eq!(trend.bound(), None);
+        assert_eq!(hints(&mut trend, Some(20), &populations), vec![39]);
+        assert!(hints(&mut trend, Some(20), &populations).is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+//! A full-screen display of the search, redrawn in place on each view.
+//!
+//! The terminal is switched to non-canonical mode, so that single keys can
+//! be read without waiting for `Enter`. `Ctrl-C` still works as usual.
+//! The last screen is kept after the search, followed by the summary.
+//! This is only supported on Unix, and requires the `tui` feature.
+
+use anyhow::{bail, Result};
+use std::io::{stdout, Write};
+
+/// Keys and what they do, shown in the footer.
+pub const KEYS: &str = "q: save and quit  p: pause  +/-: generation  s: save  j/k: scroll";
+
+/// What the full-screen display shows, independent of the search.
+#[derive(Clone, Debug, Default)]
+pub struct TuiView {
+    /// Offset of the displayed generation from the current one.
+    pub gen_offset: i32,
+    /// The first row of the world in the viewport.
+    pub scroll: i32,
+    /// Whether the search is paused.
+    pub paused: bool,
+    /// Message about the last result or action.
+    pub message: Option<String>,
+}
+
+/// Restores the terminal when dropped.
+pub struct Guard {
+    #[cfg(all(unix, feature = "tui"))]
+    termios: libc::termios,
+}
+
+#[cfg(all(unix, feature = "tui"))]
+mod imp {
+    use super::Guard;
+    use std::io::{stdin, stdout, IsTerminal};
+
+    pub fn enter() -> Option<Guard> {
+        if !stdin().is_terminal() || !stdout().is_terminal() {
+            return None;
+        }
+        let mut termios = std::mem::MaybeUninit::<libc::termios>::uninit();
+        // Safety: `tcgetattr` only writes to the given struct.
+        let termios = unsafe {
+            if libc::tcgetattr(libc::STDIN_FILENO, termios.as_mut_ptr()) != 0 {
+                return None;
+            }
+            termios.assume_init()
+        };
+        let mut raw = termios;
+        raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+        raw.c_cc[libc::VMIN] = 0;
+        raw.c_cc[libc::VTIME] = 0;
+        // Safety: `raw` is a valid `termios` obtained from `tcgetattr`.
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) } != 0 {
+            return None;
+        }
+        Some(Guard { termios })
+    }
+
+    pub fn leave(guard: &Guard) {
+        // Safety: `guard.termios` is the original state of the terminal.
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &guard.termios);
+        }
+    }
+
+    /// Reads a key, waiting at most `timeout_ms` milliseconds.
+    pub fn read_key(timeout_ms: i32) -> Option<u8> {
+        let mut fd = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // Safety: `fd` is a valid `pollfd`, and `key` is a valid buffer of
+        // one byte.
+        unsafe {
+            if libc::poll(&mut fd, 1, timeout_ms) <= 0 {
+                return None;
+            }
+            let mut key = 0u8;
+            if libc::read(libc::STDIN_FILENO, (&mut key as *mut u8).cast(), 1) == 1 {
+                Some(key)
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Whether a key is waiting to be read.
+    pub fn key_pending() -> bool {
+        let mut fd = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // Safety: `fd` is a valid `pollfd`.
+        unsafe { libc::poll(&mut fd, 1, 0) > 0 }
+    }
+}
+
+#[cfg(not(all(unix, feature = "tui")))]
+mod imp {
+    use super::Guard;
+
+    pub fn enter() -> Option<Guard> {
+        None
+    }
+
+    pub fn leave(_: &Guard) {}
+
+    pub fn read_key(_: i32) -> Option<u8> {
+        None
+    }
+
+    pub fn key_pending() -> bool {
+        false
+    }
+}
+
+/// Fails if this build does not support the full-screen display.
+pub fn require() -> Result<()> {
+    if !cfg!(feature = "tui") {
+        bail!("This build lacks the `tui` feature.")
+    }
+    Ok(())
+}
+
+/// Switches the terminal to the full-screen display.
+///
+/// Returns `None` if the input or the output is not a terminal, in which
+/// case the usual scrolling display should be used.
+pub fn enter() -> Option<Guard> {
+    let guard = imp::enter()?;
+    // Clears the screen and hides the cursor.
+    print!("\x1b[2J\x1b[?25l");
+    stdout().flush().ok();
+    Some(guard)
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        imp::leave(self);
+        print!("\x1b[?25h");
+        stdout().flush().ok();
+    }
+}
+
+/// Reads a key if there is one, waiting at most `timeout_ms` milliseconds.
+pub fn read_key(timeout_ms: i32) -> Option<u8> {
+    imp::read_key(timeout_ms)
+}
+
+/// Whether a key is waiting to be read.
+pub fn key_pending() -> bool {
+    imp::key_pending()
+}
+
+/// Redraws the whole screen with the given lines.
+pub fn draw(lines: &[String]) {
+    let mut screen = String::from("\x1b[H");
+    for line in lines {
+        screen += line;
+        screen += "\x1b[K\n";
+    }
+    screen += "\x1b[J";
+    print!("{}", screen);
+    stdout().flush().ok();
+}
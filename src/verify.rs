@@ -0,0 +1,323 @@
+//! Verification of the results, by simulating them for one period.
+//!
+//! This is independent of the search, so that it catches the results
+//! which do not really move as expected. Rules with `B0` are not
+//! supported, since their background alternates between the states.
+
+use anyhow::{anyhow, bail, Result};
+use ca_rules::{ParseNtLife, ParseNtLifeGen};
+use rlifesrc_lib::{PolyWorld, State, ALIVE, DEAD};
+use std::collections::HashMap;
+
+/// The neighbors of a cell, in the order of the bits of the neighborhood
+/// descriptions in rlifesrc, from the highest bit to the lowest.
+const NBHD: [(i32, i32); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A rule, given by the neighborhoods where a cell is born or survives.
+struct Rule {
+    b: [bool; 256],
+    s: [bool; 256],
+    /// Number of states, which is 2 for non-Generations rules.
+    gen: usize,
+}
+
+impl Rule {
+    fn new(b: Vec<u8>, s: Vec<u8>, gen: usize) -> Self {
+        let mut rule = Rule {
+            b: [false; 256],
+            s: [false; 256],
+            gen,
+        };
+        for n in b {
+            rule.b[n as usize] = true;
+        }
+        for n in s {
+            rule.s[n as usize] = true;
+        }
+        rule
+    }
+
+    /// Parses a rule, which fails for rules with `B0`, since the simulation
+    /// assumes that the background stays dead.
+    fn parse(rule_string: &str) -> Result<Self> {
+        let rule = <Rule as ParseNtLife>::parse_rule(rule_string)
+            .or_else(|_| <Rule as ParseNtLifeGen>::parse_rule(rule_string))
+            .map_err(|e| anyhow!("Cannot parse the rule {}: {}", rule_string, e))?;
+        if rule.b[0] {
+            bail!("Rules with B0, such as {}, are not supported", rule_string);
+        }
+        Ok(rule)
+    }
+
+    /// The state of a cell in the next generation.
+    fn next(&self, state: State, nbhd: u8) -> State {
+        match state {
+            DEAD if self.b[nbhd as usize] => ALIVE,
+            DEAD => DEAD,
+            ALIVE if self.s[nbhd as usize] => ALIVE,
+            ALIVE if self.gen > 2 => State(2),
+            State(i) if i + 1 < self.gen => State(i + 1),
+            _ => DEAD,
+        }
+    }
+}
+
+impl ParseNtLife for Rule {
+    fn from_bs(b: Vec<u8>, s: Vec<u8>) -> Self {
+        Rule::new(b, s, 2)
+    }
+}
+
+impl ParseNtLifeGen for Rule {
+    fn from_bsg(b: Vec<u8>, s: Vec<u8>, gen: usize) -> Self {
+        Rule::new(b, s, gen)
+    }
+}
+
+/// The cells which are not dead, on an infinite plane.
+type Cells = HashMap<(i32, i32), State>;
+
+/// A two-state pattern, as rows of cells.
+type Grid = Vec<Vec<bool>>;
+
+/// A pattern, as rows of the states of the cells.
+pub type StateGrid = Vec<Vec<State>>;
+
+/// The phases of a spaceship, and how far it moves in a period.
+type Phases = (Vec<Grid>, (i32, i32));
+
+/// Computes the next generation.
+fn step(rule: &Rule, cells: &Cells) -> Cells {
+    let mut candidates: Vec<_> = cells.keys().copied().collect();
+    for &(x, y) in cells.keys() {
+        candidates.extend(NBHD.iter().map(|(nx, ny)| (x + nx, y + ny)));
+    }
+    let mut next = Cells::new();
+    for (x, y) in candidates {
+        if next.contains_key(&(x, y)) {
+            continue;
+        }
+        let mut nbhd = 0;
+        for (nx, ny) in NBHD.iter() {
+            nbhd <<= 1;
+            if cells.get(&(x + nx, y + ny)) == Some(&ALIVE) {
+                nbhd |= 1;
+            }
+        }
+        let state = rule.next(*cells.get(&(x, y)).unwrap_or(&DEAD), nbhd);
+        if state != DEAD {
+            next.insert((x, y), state);
+        }
+    }
+    next
+}
+
+/// The position of the bounding box of the cells, and the states of the
+/// cells in it.
+fn trim_states(cells: &Cells) -> ((i32, i32), StateGrid) {
+    let min_x = cells.keys().map(|c| c.0).min().unwrap_or(0);
+    let max_x = cells.keys().map(|c| c.0).max().unwrap_or(-1);
+    let min_y = cells.keys().map(|c| c.1).min().unwrap_or(0);
+    let max_y = cells.keys().map(|c| c.1).max().unwrap_or(-1);
+    let grid = (min_y..=max_y)
+        .map(|y| {
+            (min_x..=max_x)
+                .map(|x| *cells.get(&(x, y)).unwrap_or(&DEAD))
+                .collect()
+        })
+        .collect();
+    ((min_x, min_y), grid)
+}
+
+/// The position of the bounding box of the cells, and the cells in it.
+fn trim(cells: &Cells) -> ((i32, i32), Grid) {
+    let (position, grid) = trim_states(cells);
+    let grid = grid
+        .into_iter()
+        .map(|row| row.into_iter().map(|state| state != DEAD).collect())
+        .collect();
+    (position, grid)
+}
+
+/// The first `gens` generations of a pattern with the given cells, each
+/// trimmed to its bounding box.
+///
+/// Unlike [`phases`], this keeps the states of Generations rules. Fails for
+/// rules with `B0`.
+pub fn evolve(
+    rule_string: &str,
+    cells: &[((i32, i32), State)],
+    gens: i32,
+) -> Result<Vec<StateGrid>> {
+    let rule = Rule::parse(rule_string)?;
+    let mut cells: Cells = cells
+        .iter()
+        .copied()
+        .filter(|&(_, state)| state != DEAD)
+        .collect();
+    let mut grids = Vec::new();
+    for t in 0..gens {
+        if t > 0 {
+            cells = step(&rule, &cells);
+        }
+        grids.push(trim_states(&cells).1);
+    }
+    Ok(grids)
+}
+
+/// All phases of a spaceship with the given living cells, each trimmed to
+/// its bounding box, and how far it moves in a period.
+///
+/// Returns `None` if it does not become itself translated after a period,
+/// and fails for rules with `B0`.
+pub fn phases(rule_string: &str, living: &[(i32, i32)], period: i32) -> Result<Option<Phases>> {
+    let rule = Rule::parse(rule_string)?;
+    let mut cells: Cells = living.iter().map(|&c| (c, ALIVE)).collect();
+    let (start, first) = trim(&cells);
+    let mut phases = vec![first];
+    for _ in 1..period {
+        cells = step(&rule, &cells);
+        phases.push(trim(&cells).1);
+    }
+    let (end, last) = trim(&step(&rule, &cells));
+    if cells.is_empty() || last != phases[0] {
+        return Ok(None);
+    }
+    Ok(Some((phases, (end.0 - start.0, end.1 - start.1))))
+}
+
+/// Checks that the first generation of the world, after one period,
+/// becomes itself translated by the given translation.
+///
+/// Fails for rules with `B0`, so that the result is reported as not
+/// verified instead of wrong.
+pub fn verify(world: &PolyWorld) -> Result<bool> {
+    let config = world.config();
+    let rule = Rule::parse(&config.rule_string)?;
+    let mut cells = Cells::new();
+    for x in 0..config.width {
+        for y in 0..config.height {
+            match world.get_cell_state((x, y, 0)) {
+                Some(DEAD) => {}
+                Some(state) => {
+                    cells.insert((x, y), state);
+                }
+                None => return Ok(false),
+            }
+        }
+    }
+    let expected: Cells = cells
+        .iter()
+        .map(|(&(x, y), &state)| ((x - config.dx, y - config.dy), state))
+        .collect();
+    for _ in 0..config.period {
+        cells = step(&rule, &cells);
+    }
+    Ok(cells == expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rlifesrc_lib::{Config, Status};
+
+    const GLIDER: [(i32, i32); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+    const LWSS: [(i32, i32); 9] = [
+        (1, 0),
+        (4, 0),
+        (0, 1),
+        (0, 2),
+        (4, 2),
+        (0, 3),
+        (1, 3),
+        (2, 3),
+        (3, 3),
+    ];
+
+    #[test]
+    fn phases_of_spaceships() {
+        let (glider, translation) = phases("B3/S23", &GLIDER, 4).unwrap().unwrap();
+        assert_eq!(glider.len(), 4);
+        assert_eq!(translation, (1, 1));
+        assert!(glider
+            .iter()
+            .all(|phase| phase.len() == 3 && phase[0].len() == 3));
+        let (lwss, translation) = phases("B3/S23", &LWSS, 4).unwrap().unwrap();
+        assert_eq!(translation, (-2, 0));
+        // The LWSS has 9 cells in two phases, and 12 in the other two.
+        let cells: Vec<_> = lwss
+            .iter()
+            .map(|phase| phase.iter().flatten().filter(|&&alive| alive).count())
+            .collect();
+        assert_eq!(cells, [9, 12, 9, 12]);
+    }
+
+    #[test]
+    fn phases_of_other_patterns() {
+        // A block does not move, and a single cell dies.
+        let block = [(0, 0), (1, 0), (0, 1), (1, 1)];
+        let (_, translation) = phases("B3/S23", &block, 1).unwrap().unwrap();
+        assert_eq!(translation, (0, 0));
+        assert!(phases("B3/S23", &[(0, 0)], 1).unwrap().is_none());
+        // The glider does not come back after 2 generations.
+        assert!(phases("B3/S23", &GLIDER, 2).unwrap().is_none());
+        assert!(phases("bad rule", &GLIDER, 4).is_err());
+    }
+
+    #[test]
+    fn generations_rules() {
+        // In Brian's Brain, a living cell always dies, and then decays.
+        let rule = Rule::parse("/2/3").unwrap();
+        let mut cells: Cells = [((0, 0), ALIVE), ((1, 0), ALIVE)].iter().copied().collect();
+        cells = step(&rule, &cells);
+        assert_eq!(cells[&(0, 0)], State(2));
+        assert_eq!(cells[&(0, 1)], ALIVE);
+        assert_eq!(cells[&(1, -1)], ALIVE);
+        cells = step(&rule, &cells);
+        assert!(!cells.contains_key(&(0, 0)));
+    }
+
+    #[test]
+    fn evolve_keeps_the_states() {
+        let cells = [((0, 0), ALIVE), ((1, 0), ALIVE), ((5, 5), DEAD)];
+        let grids = evolve("/2/3", &cells, 2).unwrap();
+        assert_eq!(grids[0], [[ALIVE, ALIVE]]);
+        let dying = State(2);
+        assert_eq!(grids[1], [[ALIVE, ALIVE], [dying, dying], [ALIVE, ALIVE]]);
+        assert!(evolve("B03/S23", &cells, 2).is_err());
+    }
+
+    #[test]
+    fn b0_rules() {
+        // The background of this rule alternates between dead and alive,
+        // so the cells cannot be simulated on a dead background.
+        let rule = "B03/S23";
+        assert!(Rule::parse(rule).is_err());
+        assert!(phases(rule, &GLIDER, 4).is_err());
+        let world = Config::new(5, 5, 4)
+            .set_translate(1, 1)
+            .set_rule_string(rule)
+            .world()
+            .unwrap();
+        assert!(verify(&world).is_err());
+    }
+
+    #[test]
+    fn verify_worlds() {
+        let mut world = Config::new(5, 5, 4).set_translate(1, 1).world().unwrap();
+        // The world is unknown before the search.
+        assert!(!verify(&world).unwrap());
+        assert_eq!(world.search(None), Status::Found);
+        assert!(verify(&world).unwrap());
+    }
+}
@@ -0,0 +1,421 @@
+//! Searches of the binary with tiny targets, which end in milliseconds.
+
+use std::{
+    collections::HashSet,
+    env,
+    fs::{read, read_dir, read_to_string, remove_dir_all},
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+/// A result directory for a test, which does not exist yet, and is removed
+/// when the test ends.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = env::temp_dir().join(format!("spaceships-cli-{}-{}", name, std::process::id()));
+        if dir.exists() {
+            remove_dir_all(&dir).unwrap();
+        }
+        TempDir(dir)
+    }
+}
+
+impl std::ops::Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if self.0.exists() {
+            remove_dir_all(&self.0).unwrap();
+        }
+    }
+}
+
+/// Runs the binary with the results in `dir`.
+fn run(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_spaceships"))
+        .arg("-d")
+        .arg(dir)
+        .arg("-q")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Runs the binary, and checks that it succeeds.
+fn run_ok(dir: &Path, args: &[&str]) -> String {
+    success(run(dir, args))
+}
+
+/// Runs a subcommand of the binary on the result directory `dir`, and
+/// checks that it succeeds.
+fn run_subcommand(dir: &Path, subcommand: &str, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_spaceships"))
+        .arg(subcommand)
+        .arg("-d")
+        .arg(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    success(output)
+}
+
+/// Checks that the binary succeeded, and returns its output.
+fn success(output: Output) -> String {
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        output.status.success(),
+        "{}{}",
+        stdout,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    stdout
+}
+
+/// A search for the glider, which is found at height 3.
+const GLIDER: &[&str] = &["-p", "4", "-x", "1", "-y", "1", "-w", "5"];
+
+/// A search for the orthogonal c/2 spaceships, found from height 4 on.
+const SHIPS: &[&str] = &["-p", "4", "-x", "2", "-y", "0", "-w", "7"];
+
+/// Searches for the glider in a new result directory, with more arguments.
+fn glider(name: &str, args: &[&str]) -> (TempDir, String) {
+    let dir = TempDir::new(name);
+    let stdout = run_ok(&dir, &[GLIDER, args].concat());
+    (dir, stdout)
+}
+
+/// The names of the result files in `dir`.
+fn results(dir: &Path) -> Vec<String> {
+    let mut names: Vec<_> = read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".rle"))
+        .collect();
+    names.sort();
+    names
+}
+
+/// The lines of a result file, without the comments that depend on the run.
+fn content(path: &Path) -> Vec<String> {
+    read_to_string(path)
+        .unwrap()
+        .lines()
+        .filter(|line| !line.starts_with("#C Date") && !line.starts_with("#C Search time"))
+        .map(String::from)
+        .collect()
+}
+
+/// The living cells of a two-state RLE file, moved to the origin.
+fn cells(path: &Path) -> HashSet<(i32, i32)> {
+    let rle = read_to_string(path).unwrap();
+    let mut cells = HashSet::new();
+    let (mut x, mut y, mut run) = (0, 0, 0);
+    let lines = rle.lines().filter(|line| !line.starts_with('#'));
+    for c in lines.skip(1).flat_map(|line| line.chars()) {
+        match c {
+            '0'..='9' => run = run * 10 + c.to_digit(10).unwrap() as i32,
+            'b' | 'o' | '$' => {
+                let n = run.max(1);
+                match c {
+                    'b' => x += n,
+                    'o' => {
+                        cells.extend((x..x + n).map(|x| (x, y)));
+                        x += n;
+                    }
+                    _ => {
+                        x = 0;
+                        y += n;
+                    }
+                }
+                run = 0;
+            }
+            '!' => break,
+            _ => {}
+        }
+    }
+    normalize(&cells)
+}
+
+/// Moves the cells so that the bounding box starts at the origin.
+fn normalize(cells: &HashSet<(i32, i32)>) -> HashSet<(i32, i32)> {
+    let min_x = cells.iter().map(|c| c.0).min().unwrap_or(0);
+    let min_y = cells.iter().map(|c| c.1).min().unwrap_or(0);
+    cells.iter().map(|&(x, y)| (x - min_x, y - min_y)).collect()
+}
+
+/// One generation of the Game of Life.
+fn step(cells: &HashSet<(i32, i32)>) -> HashSet<(i32, i32)> {
+    let neighbors = |(x, y): (i32, i32)| {
+        (-1..=1)
+            .flat_map(move |dx| (-1..=1).map(move |dy| (x + dx, y + dy)))
+            .filter(move |&c| c != (x, y))
+    };
+    let candidates: HashSet<_> = cells.iter().flat_map(|&c| neighbors(c)).collect();
+    candidates
+        .into_iter()
+        .filter(|&c| {
+            let n = neighbors(c).filter(|n| cells.contains(n)).count();
+            n == 3 || (n == 2 && cells.contains(&c))
+        })
+        .collect()
+}
+
+#[test]
+fn exhausted_search_prints_a_summary() {
+    let (dir, stdout) = glider("exhausted", &["--max-height", "5"]);
+    assert!(stdout.contains("Found 1 spaceship, the smallest with 5 cells (xq4_153)."));
+    assert!(stdout.contains("Exhausted heights 1 to 5"));
+    assert_eq!(results(&dir), ["5P4H1V1.rle"]);
+    let log = read_to_string(dir.join("results.jsonl")).unwrap();
+    let summary = log.lines().last().unwrap();
+    assert!(summary.contains("\"stop\":\"maximum size\""), "{}", summary);
+    assert!(summary.contains("\"best_cell_count\":5"), "{}", summary);
+}
+
+#[test]
+fn stop_after_the_first_result() {
+    let (dir, stdout) = glider("stop-after-first", &["--stop-after-first"]);
+    assert!(stdout.contains("Stopped (enough results) at height 3"));
+    assert_eq!(results(&dir), ["5P4H1V1.rle"]);
+}
+
+#[test]
+fn min_cell_count_keeps_the_bound() {
+    // The LWSS with 9 cells is found before the MWSS with 11 cells, which
+    // would be out of the bound if the skipped LWSS reduced it.
+    let all = TempDir::new("all-cell-counts");
+    run_ok(&all, &[SHIPS, &["--max-height", "7"]].concat());
+    assert_eq!(results(&all), ["9P4H2V0.rle"]);
+    let dir = TempDir::new("min-cell-count");
+    let args = ["--max-height", "7", "--min-cell-count", "10"];
+    run_ok(&dir, &[SHIPS, &args].concat());
+    assert_eq!(results(&dir), ["11P4H2V0.rle"]);
+}
+
+#[test]
+fn threads_print_summaries_and_totals() {
+    let (dir, stdout) = glider("threads", &["--max-height", "5", "--threads", "2"]);
+    assert_eq!(stdout.matches("Exhausted heights").count(), 2, "{}", stdout);
+    assert!(stdout
+        .contains("In total: 2 searches, 1 spaceship found, the smallest with 5 cells (xq4_153)"));
+    assert_eq!(results(&dir), ["5P4H1V1.rle"]);
+    let log = read_to_string(dir.join("results.jsonl")).unwrap();
+    assert_eq!(log.matches("\"summary\"").count(), 2);
+}
+
+#[test]
+fn survey_of_heights() {
+    let (dir, stdout) = glider("survey", &["--survey", "1", "--heights", "3..5"]);
+    assert!(stdout.contains("  1. height 3: found a spaceship with 5 cells"));
+    // The glider found at the other heights is already saved.
+    assert_eq!(results(&dir), ["5P4H1V1.rle"]);
+    let survey = read_to_string(dir.join("survey.json")).unwrap();
+    assert_eq!(survey.matches("\"height\"").count(), 3);
+    assert!(!dir.join("survey_H3.json").exists());
+}
+
+#[test]
+fn incompatible_symmetry() {
+    let dir = TempDir::new("symmetry");
+    let output = run(&dir, &["-p", "4", "-x", "0", "-y", "1", "-s", "D2-"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Symmetry D2- is incompatible with the translation (0, 1)"));
+    assert!(stderr.contains("Compatible symmetries: C1, D2|."));
+    assert!(!dir.exists());
+}
+
+#[test]
+fn resumed_generations_search_keeps_its_rule() {
+    let dir = TempDir::new("generations");
+    let search = [
+        "-p",
+        "1",
+        "-x",
+        "0",
+        "-y",
+        "1",
+        "-w",
+        "4",
+        "--max-height",
+        "4",
+    ];
+    let output = run(
+        &dir,
+        &[&search[..], &["-r", "/2/3", "--max-steps", "2"]].concat(),
+    );
+    assert_eq!(output.status.code(), Some(10));
+    assert!(results(&dir).is_empty());
+
+    // Resumed without `--rule`, i.e., with the default B3/S23.
+    let stdout = run_ok(
+        &dir,
+        &[&search[..], &["--name-template", "{rule}_{cells}"]].concat(),
+    );
+    assert!(stdout.contains("Warning: the save file uses the rule /2/3"));
+    assert_eq!(results(&dir), ["23_2.rle"]);
+    let rle = read_to_string(dir.join("23_2.rle")).unwrap();
+    assert!(rle.contains("#C Rule: /2/3\n"));
+    assert!(rle.contains("x = 2, y = 2, rule = /2/3\n"));
+    assert!(!rle.contains("B3/S23"));
+    let log = read_to_string(dir.join("results.jsonl")).unwrap();
+    assert!(log.contains("\"rule\":\"/2/3\""));
+    assert!(!log.contains("B3/S23"));
+}
+
+#[test]
+fn all_phases_evolve_into_each_other() {
+    let (dir, _) = glider("all-phases", &["--max-height", "5", "--all-phases"]);
+    let phases: Vec<_> = (0..4)
+        .map(|k| cells(&dir.join(format!("5P4H1V1_phases/phase_{}.rle", k))))
+        .collect();
+    assert_eq!(phases[0], cells(&dir.join("5P4H1V1.rle")));
+    for k in 0..4 {
+        assert_eq!(phases[k].len(), 5);
+        assert_eq!(normalize(&step(&phases[k])), phases[(k + 1) % 4], "{}", k);
+    }
+    assert_ne!(phases[0], phases[1]);
+}
+
+#[test]
+fn regenerate_the_cleaned_files() {
+    let mut names: Vec<_> = (0..4)
+        .map(|k| format!("5P4H1V1_phases/phase_{}.rle", k))
+        .collect();
+    let mut args = vec!["--all-phases"];
+    if cfg!(feature = "svg") {
+        names.push(String::from("5P4H1V1.svg"));
+        args.push("--svg");
+    }
+    let (dir, _) = glider("regenerate", &[&["--max-height", "5"], &args[..]].concat());
+    let before: Vec<_> = names
+        .iter()
+        .map(|name| read(dir.join(name)).unwrap())
+        .collect();
+    run_subcommand(&dir, "clean", &["--derived", "--yes"]);
+    assert!(names.iter().all(|name| !dir.join(name).exists()));
+    let stdout = run_subcommand(&dir, "regenerate", &args);
+    let regenerated = format!("Regenerated {} files.", names.len());
+    assert!(stdout.contains(&regenerated), "{}", stdout);
+    for (name, bytes) in names.iter().zip(&before) {
+        assert_eq!(&read(dir.join(name)).unwrap(), bytes, "{}", name);
+    }
+}
+
+#[test]
+fn name_template() {
+    let template = "glider {rule}/{cells}_{seq}";
+    let (dir, _) = glider(
+        "name-template",
+        &["--max-height", "5", "--name-template", template],
+    );
+    assert_eq!(results(&dir), ["glider b3s23_5_1.rle"]);
+    let log = read_to_string(dir.join("results.jsonl")).unwrap();
+    assert!(log.contains("\"file\":\"glider b3s23_5_1.rle\""));
+
+    // A different template is checked before the search starts.
+    let output = run(
+        &dir,
+        &[GLIDER, &["--name-template", "{population}"]].concat(),
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Unknown placeholder in the filename template: {population}"));
+}
+
+#[test]
+fn canonical_output_of_other_directions() {
+    let (canonical, _) = glider("canonical", &["--max-height", "5", "--canonical-output"]);
+    let expected = content(&canonical.join("5P4H1V1.rle"));
+    // Searches with negative `dx` are slow in debug builds, so only two of
+    // the other directions are tried.
+    for &(dx, dy) in &[(-1, 1), (1, -1)] {
+        let dir = TempDir::new(&format!("canonical{}{}", dx, dy));
+        let (dx_arg, dy_arg) = (format!("--dx={}", dx), format!("--dy={}", dy));
+        let args = ["-p", "4", &dx_arg, &dy_arg, "-w", "5", "--max-height", "5"];
+        run_ok(&dir, &[&args[..], &["--canonical-output"]].concat());
+        assert_eq!(results(&dir), ["5P4H1V1.rle"]);
+        assert_eq!(content(&dir.join("5P4H1V1.rle")), expected);
+    }
+}
+
+#[test]
+fn default_output_keeps_the_searched_orientation() {
+    let dir = TempDir::new("searched-orientation");
+    run_ok(
+        &dir,
+        &[
+            "-p",
+            "4",
+            "--dx=1",
+            "--dy=-1",
+            "-w",
+            "5",
+            "--max-height",
+            "5",
+        ],
+    );
+    assert_eq!(results(&dir), ["5P4H1V-1.rle"]);
+    let rle = content(&dir.join("5P4H1V-1.rle"));
+    assert!(rle.contains(&String::from("#C (1,-1)c/4 spaceship found by spaceships.")));
+    let (glider, _) = glider("unflipped", &["--max-height", "5"]);
+    assert_ne!(rle, content(&glider.join("5P4H1V1.rle")));
+}
+
+#[cfg(all(unix, feature = "hooks"))]
+#[test]
+fn hook_runs_with_the_environment() {
+    use std::{fs::write, thread, time::Duration};
+
+    let dir = TempDir::new("hook");
+    std::fs::create_dir_all(&*dir).unwrap();
+    let script = dir.join("hook.sh");
+    write(
+        &script,
+        "cat > \"$(dirname \"$SSS_FILE\")/stdin.txt\"\n\
+         echo \"$1 $SSS_CELLS $SSS_APGCODE $SSS_PERIOD $SSS_DX $SSS_DY $SSS_RULE $SSS_FILE\" \
+         > \"$(dirname \"$SSS_FILE\")/env.tmp\"\n\
+         mv \"$(dirname \"$SSS_FILE\")/env.tmp\" \"$(dirname \"$SSS_FILE\")/env.txt\"\n",
+    )
+    .unwrap();
+    let command = format!("sh '{}' {{cells}}", script.display());
+    let args = [
+        "--max-height",
+        "5",
+        "--on-found",
+        &command,
+        "--on-found-stdin",
+        "rle",
+    ];
+    run_ok(&dir, &[GLIDER, &args[..]].concat());
+
+    // The hook runs in the background.
+    let env = dir.join("env.txt");
+    for _ in 0..100 {
+        if env.exists() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    let file = dir.join("5P4H1V1.rle");
+    assert_eq!(
+        read_to_string(&env).unwrap().trim(),
+        format!("5 5 xq4_153 4 1 1 B3/S23 {}", file.display())
+    );
+    // The RLE on the standard input is the pattern in the file.
+    let stdin = read_to_string(dir.join("stdin.txt")).unwrap();
+    let pattern: Vec<_> = content(&file)
+        .into_iter()
+        .filter(|line| !line.starts_with('#'))
+        .collect();
+    assert_eq!(stdin.lines().collect::<Vec<_>>(), pattern);
+}